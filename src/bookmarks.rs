@@ -0,0 +1,129 @@
+#![allow(dead_code)]
+
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use crate::camera::{OrbitView, Projection, Tile};
+
+/// Numbered camera views (slots 0-9), kept in a per-user file so they survive restarts
+#[derive(Clone, Debug, Default)]
+pub struct Bookmarks {
+    pub views: BTreeMap<u8, OrbitView>,
+    pub path: PathBuf,
+}
+
+impl Bookmarks {
+    /// `$XDG_CONFIG_HOME/sol/marcadores.ron`, `~/.config/sol/marcadores.ron` or
+    /// `%APPDATA%\sol\marcadores.ron`; the working directory when none is set
+    pub fn user_file() -> PathBuf {
+        let config = std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from));
+        match config {
+            Some(dir) => dir.join("sol").join("marcadores.ron"),
+            None => PathBuf::from("marcadores.ron"),
+        }
+    }
+
+    /// Reads the bookmarks in `path`; a missing file is just an empty set
+    pub fn load(path: &Path) -> Result<Bookmarks, String> {
+        let mut bookmarks = Bookmarks { views: BTreeMap::new(), path: path.to_path_buf() };
+        if !path.exists() {
+            return Ok(bookmarks);
+        }
+        let source = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let desc: BTreeMap<u8, BookmarkDesc> = ron::from_str(&source).map_err(|e| format!("{}: {e}", path.display()))?;
+        for (slot, view) in desc {
+            if slot > 9 {
+                return Err(format!("{}: marcador {slot} fuera de rango (0-9)", path.display()));
+            }
+            if !view.distance.is_finite() || view.distance <= 0.0 {
+                return Err(format!("{}: marcador {slot}: la distancia debe ser positiva", path.display()));
+            }
+            let view: OrbitView = view.into();
+            // Same limits `scene_file` puts on the scene camera
+            match view.projection {
+                Projection::Perspective { fov_y } | Projection::OffAxis { fov_y, .. } if !(fov_y > 0.0 && fov_y < std::f32::consts::PI) => {
+                    return Err(format!("{}: marcador {slot}: fov_y debe estar entre 0 y 180 grados, no {}", path.display(), fov_y.to_degrees()));
+                }
+                Projection::Orthographic { height } if !(height.is_finite() && height > 0.0) => {
+                    return Err(format!("{}: marcador {slot}: la altura ortográfica debe ser positiva, no {height}", path.display()));
+                }
+                _ => {}
+            }
+            bookmarks.views.insert(slot, view);
+        }
+        Ok(bookmarks)
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+        }
+        let desc: BTreeMap<u8, BookmarkDesc> = self.views.iter().map(|(&slot, view)| (slot, BookmarkDesc::from(view))).collect();
+        let text = ron::ser::to_string_pretty(&desc, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, text + "\n").map_err(|e| format!("{}: {e}", self.path.display()))
+    }
+
+    pub fn get(&self, slot: u8) -> Option<OrbitView> {
+        self.views.get(&slot).copied()
+    }
+
+    pub fn store(&mut self, slot: u8, view: OrbitView) {
+        self.views.insert(slot, view);
+    }
+}
+
+// --- File format (RON); angles in degrees for readability ---
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BookmarkDesc {
+    yaw: f32,
+    pitch: f32,
+    distance: f32,
+    target: (f32, f32, f32),
+    projection: ProjectionDesc,
+}
+
+/// The off-axis tile belongs to the window, not to the view, so it is not saved
+#[derive(Serialize, Deserialize)]
+enum ProjectionDesc {
+    Perspective { fov_y: f32 },
+    Orthographic { height: f32 },
+    OffAxis { fov_y: f32 },
+}
+
+impl From<&OrbitView> for BookmarkDesc {
+    fn from(view: &OrbitView) -> Self {
+        BookmarkDesc {
+            yaw: view.yaw.to_degrees(),
+            pitch: view.pitch.to_degrees(),
+            distance: view.distance,
+            target: (view.target.x, view.target.y, view.target.z),
+            projection: match view.projection {
+                Projection::Perspective { fov_y } => ProjectionDesc::Perspective { fov_y: fov_y.to_degrees() },
+                Projection::Orthographic { height } => ProjectionDesc::Orthographic { height },
+                Projection::OffAxis { fov_y, .. } => ProjectionDesc::OffAxis { fov_y: fov_y.to_degrees() },
+            },
+        }
+    }
+}
+
+impl From<BookmarkDesc> for OrbitView {
+    fn from(desc: BookmarkDesc) -> Self {
+        OrbitView {
+            yaw: desc.yaw.to_radians(),
+            pitch: desc.pitch.to_radians(),
+            distance: desc.distance,
+            target: Vector3::new(desc.target.0, desc.target.1, desc.target.2),
+            projection: match desc.projection {
+                ProjectionDesc::Perspective { fov_y } => Projection::Perspective { fov_y: fov_y.to_radians() },
+                ProjectionDesc::Orthographic { height } => Projection::Orthographic { height },
+                // The recalling camera swaps in its own tile (see `Camera::glide_to`)
+                ProjectionDesc::OffAxis { fov_y } => Projection::OffAxis { fov_y: fov_y.to_radians(), tile: Tile::single() },
+            },
+        }
+    }
+}
@@ -1,24 +1,215 @@
 #![allow(dead_code)]
 
 use raylib::prelude::*;
-use crate::matrix::create_view_matrix;
+use crate::camera_path::{CameraPose, Ease};
+use crate::input::{Action, InputMap};
+use crate::transform::Quat;
+use crate::matrix::{create_frustum_matrix, create_orthographic_matrix, create_projection_matrix, create_view_matrix};
 use std::f32::consts::PI;
 
+/// One tile of a larger view split into a grid, e.g. one monitor of a video wall.
+/// Row 0 is the top row, column 0 the left one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tile {
+    pub columns: u32,
+    pub rows: u32,
+    pub column: u32,
+    pub row: u32,
+}
+
+impl Tile {
+    pub fn single() -> Self {
+        Tile { columns: 1, rows: 1, column: 0, row: 0 }
+    }
+
+    pub fn is_single(&self) -> bool {
+        self.columns <= 1 && self.rows <= 1
+    }
+}
+
+/// How the camera maps view space to clip space
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Projection {
+    Perspective { fov_y: f32 },              // vertical field of view, radians
+    Orthographic { height: f32 },            // visible height in world units (width from the aspect)
+    OffAxis { fov_y: f32, tile: Tile },      // `fov_y` spans all rows of the tile grid
+}
+
+impl Default for Projection {
+    fn default() -> Self {
+        Projection::Perspective { fov_y: PI / 3.0 }
+    }
+}
+
+impl Projection {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Projection::Perspective { .. } => "perspectiva",
+            Projection::Orthographic { .. } => "ortográfica",
+            Projection::OffAxis { .. } => "descentrada",
+        }
+    }
+
+    pub fn matrix(&self, aspect: f32, near: f32, far: f32) -> Matrix {
+        match *self {
+            Projection::Perspective { fov_y } => create_projection_matrix(fov_y, aspect, near, far),
+            Projection::Orthographic { height } => {
+                let (half_w, half_h) = (height * aspect * 0.5, height * 0.5);
+                create_orthographic_matrix(-half_w, half_w, -half_h, half_h, near, far)
+            }
+            Projection::OffAxis { fov_y, tile } => {
+                // Frustum of the whole wall, then the window of this tile on its near plane
+                let columns = tile.columns.max(1) as f32;
+                let rows = tile.rows.max(1) as f32;
+                let top = near * (fov_y * 0.5).tan();
+                let right = top * aspect * columns / rows;
+                let (w, h) = (2.0 * right / columns, 2.0 * top / rows);
+                let left = -right + tile.column as f32 * w;
+                let upper = top - tile.row as f32 * h;
+                create_frustum_matrix(left, left + w, upper - h, upper, near, far)
+            }
+        }
+    }
+
+    /// The projection of `like`'s kind that frames the same as this one at `distance`
+    pub fn matching(&self, like: &Projection, distance: f32) -> Projection {
+        match (*self, *like) {
+            (Projection::Perspective { fov_y } | Projection::OffAxis { fov_y, .. }, Projection::Orthographic { .. }) => {
+                Projection::Orthographic { height: 2.0 * distance * (fov_y * 0.5).tan() }
+            }
+            (Projection::Orthographic { height }, Projection::Perspective { .. }) => {
+                Projection::Perspective { fov_y: 2.0 * (height * 0.5 / distance).atan() }
+            }
+            (Projection::Orthographic { height }, Projection::OffAxis { tile, .. }) => {
+                Projection::OffAxis { fov_y: 2.0 * (height * 0.5 / distance).atan(), tile }
+            }
+            (Projection::Perspective { fov_y }, Projection::OffAxis { tile, .. }) => Projection::OffAxis { fov_y, tile },
+            (Projection::OffAxis { fov_y, .. }, Projection::Perspective { .. }) => Projection::Perspective { fov_y },
+            _ => *self,
+        }
+    }
+
+    /// Blends two projections of the same kind; a different kind jumps straight to `other`
+    pub fn interpolate(&self, other: &Projection, t: f32) -> Projection {
+        match (*self, *other) {
+            (Projection::Perspective { fov_y: a }, Projection::Perspective { fov_y: b }) => {
+                Projection::Perspective { fov_y: a + (b - a) * t }
+            }
+            (Projection::Orthographic { height: a }, Projection::Orthographic { height: b }) => {
+                Projection::Orthographic { height: a * (b / a).powf(t) }
+            }
+            (Projection::OffAxis { fov_y: a, .. }, Projection::OffAxis { fov_y: b, tile }) => {
+                Projection::OffAxis { fov_y: a + (b - a) * t, tile }
+            }
+            _ => *other,
+        }
+    }
+}
+
+/// An orbit view as saved in a bookmark: angles and distance around a target, and the projection
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OrbitView {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub distance: f32,
+    pub target: Vector3,
+    pub projection: Projection,
+}
+
+impl OrbitView {
+    /// Yaw turns the short way round and the distance changes exponentially, so zooming
+    /// far out or back in takes the same time; the projection changes to `other`'s kind at
+    /// once, matched to the current framing, and then blends
+    pub fn interpolate(&self, other: &OrbitView, t: f32) -> OrbitView {
+        let mut turn = (other.yaw - self.yaw).rem_euclid(2.0 * PI);
+        if turn > PI {
+            turn -= 2.0 * PI;
+        }
+        OrbitView {
+            yaw: self.yaw + turn * t,
+            pitch: self.pitch + (other.pitch - self.pitch) * t,
+            distance: self.distance * (other.distance / self.distance).powf(t),
+            target: self.target.lerp(other.target, t),
+            projection: self.projection.matching(&other.projection, self.distance).interpolate(&other.projection, t),
+        }
+    }
+}
+
+/// A bookmark being recalled
+#[derive(Clone, Copy, Debug)]
+struct Glide {
+    from: OrbitView,
+    to: OrbitView,
+    elapsed: f32,
+    duration: f32,
+}
+
+/// How input moves the camera
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CameraMode {
+    Orbit,   // around a free target that can be panned
+    FreeFly, // 6DOF flight with quaternion orientation
+    Follow,  // orbit around an entity, tracked with smoothing
+}
+
+impl CameraMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CameraMode::Orbit => "órbita",
+            CameraMode::FreeFly => "vuelo libre",
+            CameraMode::Follow => "seguimiento",
+        }
+    }
+}
+
 pub struct Camera {
     // Camera position/orientation
     pub eye: Vector3,        // Camera position
     pub target: Vector3,     // Point the camera is looking at
     pub up: Vector3,         // Up vector
 
+    pub mode: CameraMode,
+
     // Orbit camera parameters
     pub yaw: f32,            // Rotation around Y axis (left/right)
     pub pitch: f32,          // Rotation around X axis (up/down)
     pub distance: f32,       // Distance from target
 
-    // Movement speed
-    pub rotation_speed: f32,
-    pub zoom_speed: f32,
-    pub pan_speed: f32,
+    // Free-fly parameters
+    pub orientation: Quat,   // looks down its local -Z, up is local +Y
+    pub fly_speed: f32,      // world units/s
+    pub boost: f32,          // speed factor while FlyBoost is held
+
+    // Follow parameters
+    pub follow: Option<String>, // entity tracked in follow mode
+    pub follow_smoothing: f32,  // 1/s, how quickly the target catches up with the entity
+
+    // Movement speed, per second so motion does not depend on the frame rate
+    pub rotation_speed: f32,     // radians/s with keys or sticks
+    pub zoom_speed: f32,         // e-folds of distance/s (exponential zoom)
+    pub pan_speed: f32,          // fractions of the distance/s
+    pub mouse_sensitivity: f32,  // radians per pixel dragged
+    pub wheel_step: f32,         // e-folds of distance per wheel notch
+
+    // Inertia: after input stops the camera keeps moving and slows down
+    pub inertia: bool,
+    pub damping: f32,            // 1/s, how fast the glide dies out
+    yaw_velocity: f32,
+    pitch_velocity: f32,
+    roll_velocity: f32,
+    zoom_velocity: f32,          // d(ln distance)/dt
+    zoom_pending: f32,           // wheel zoom (ln distance) not applied yet
+    pan_velocity: Vector3,       // world units/s
+    fly_velocity: Vector3,       // world units/s
+    glide: Option<Glide>,        // eased move to a recalled bookmark; input waits until it ends
+
+    // Projection
+    pub projection: Projection,
+    pub near: f32,
+    pub far: f32,
+    pub aspect: f32,         // width / height of the viewport, see `set_viewport`
+    pub viewport_height: f32, // pixels, to pan the point under the cursor along with it
+    pub tile: Tile,          // grid used when switching to the off-axis projection
 }
 
 impl Camera {
@@ -38,12 +229,80 @@ impl Camera {
             eye,
             target,
             up,
+            mode: CameraMode::Orbit,
             yaw,
             pitch,
             distance,
-            rotation_speed: 0.05,
-            zoom_speed: 0.5,
-            pan_speed: 0.1,
+            orientation: Quat::look_rotation(target - eye, up),
+            fly_speed: 8.0,
+            boost: 4.0,
+            follow: None,
+            follow_smoothing: 6.0,
+            rotation_speed: 2.0,
+            zoom_speed: 1.0,
+            pan_speed: 0.5,
+            mouse_sensitivity: 0.006,
+            wheel_step: 0.12,
+            inertia: true,
+            damping: 6.0,
+            yaw_velocity: 0.0,
+            pitch_velocity: 0.0,
+            roll_velocity: 0.0,
+            zoom_velocity: 0.0,
+            zoom_pending: 0.0,
+            pan_velocity: Vector3::zero(),
+            fly_velocity: Vector3::zero(),
+            glide: None,
+            projection: Projection::default(),
+            near: 0.5,
+            far: 100.0,
+            aspect: 1.0,
+            viewport_height: 1.0,
+            tile: Tile::single(),
+        }
+    }
+
+    /// Call whenever the render target changes size; the projection follows the new aspect
+    pub fn set_viewport(&mut self, width: f32, height: f32) {
+        if width > 0.0 && height > 0.0 {
+            self.aspect = width / height;
+            self.viewport_height = height;
+        }
+    }
+
+    pub fn get_projection_matrix(&self) -> Matrix {
+        self.projection.matrix(self.aspect, self.near, self.far)
+    }
+
+    /// Vertical field of view, or for orthographic the equivalent angle at the target distance
+    pub fn fov_y(&self) -> f32 {
+        match self.projection {
+            Projection::Perspective { fov_y } | Projection::OffAxis { fov_y, .. } => fov_y,
+            Projection::Orthographic { height } => 2.0 * (height * 0.5 / self.distance).atan(),
+        }
+    }
+
+    /// Perspective -> orthographic -> off-axis (only with a tile grid) -> perspective.
+    /// The orthographic extent matches what the perspective showed at the target, so the
+    /// framing stays put.
+    pub fn cycle_projection(&mut self) {
+        let fov_y = self.fov_y();
+        self.projection = match self.projection {
+            Projection::Perspective { .. } => Projection::Orthographic {
+                height: 2.0 * self.distance * (fov_y * 0.5).tan(),
+            },
+            Projection::Orthographic { .. } if !self.tile.is_single() => Projection::OffAxis { fov_y, tile: self.tile },
+            _ => Projection::Perspective { fov_y },
+        };
+    }
+
+    /// Narrows (`factor` < 1) or widens the view: FOV for perspective, extents for orthographic
+    pub fn zoom_projection(&mut self, factor: f32) {
+        match &mut self.projection {
+            Projection::Perspective { fov_y } | Projection::OffAxis { fov_y, .. } => {
+                *fov_y = (*fov_y * factor).clamp(5f32.to_radians(), 150f32.to_radians());
+            }
+            Projection::Orthographic { height } => *height = (*height * factor).max(0.01),
         }
     }
 
@@ -70,93 +329,318 @@ impl Camera {
         self.update_eye_position();
     }
 
-    /// Process keyboard input to control the camera
-    pub fn process_input(&mut self, window: &RaylibHandle) {
-        // Rotation controls (yaw)
-        if window.is_key_down(KeyboardKey::KEY_A) {
-            self.yaw += self.rotation_speed;
+    /// Moves eye and target together, keeping the orbit around the target
+    pub fn translate(&mut self, offset: Vector3) {
+        self.target += offset;
+        if self.mode == CameraMode::FreeFly {
+            self.eye += offset;
+        } else {
             self.update_eye_position();
         }
-        if window.is_key_down(KeyboardKey::KEY_D) {
-            self.yaw -= self.rotation_speed;
+    }
+
+    /// Switches mode without moving the view: flight starts looking where the orbit
+    /// looked, and the orbit resumes around the point the flight was looking at
+    pub fn set_mode(&mut self, mode: CameraMode) {
+        if mode == self.mode {
+            return;
+        }
+        let world_up = Vector3::new(0.0, 1.0, 0.0);
+        if mode == CameraMode::FreeFly {
+            self.orientation = Quat::look_rotation(self.target - self.eye, world_up);
+        } else if self.mode == CameraMode::FreeFly {
+            // Roll is dropped: the orbit keeps the world up
+            self.up = world_up;
+            let direction = self.eye - self.target;
+            self.pitch = (direction.y / self.distance).clamp(-1.0, 1.0).asin();
+            self.yaw = direction.z.atan2(direction.x);
             self.update_eye_position();
         }
+        self.mode = mode;
+        self.stop();
+    }
 
-        // Rotation controls (pitch)
-        if window.is_key_down(KeyboardKey::KEY_W) {
-            self.pitch += self.rotation_speed;
-            self.update_eye_position();
+    /// Current eye, target and FOV, e.g. to record a camera path keyframe
+    pub fn pose(&self) -> CameraPose {
+        CameraPose { eye: self.eye, target: self.target, fov_y: self.fov_y() }
+    }
+
+    /// Jumps to `pose` (camera path playback). The orbit angles and the flight
+    /// orientation are rebuilt from it, so any mode carries on from there; the FOV
+    /// only applies to perspective projections.
+    pub fn apply_pose(&mut self, pose: &CameraPose) {
+        let world_up = Vector3::new(0.0, 1.0, 0.0);
+        let direction = pose.eye - pose.target;
+        self.eye = pose.eye;
+        self.target = pose.target;
+        self.up = world_up;
+        self.distance = direction.length().max(1e-3);
+        self.pitch = (direction.y / self.distance).clamp(-1.0, 1.0).asin();
+        self.yaw = direction.z.atan2(direction.x);
+        self.orientation = Quat::look_rotation(pose.target - pose.eye, world_up);
+        if let Projection::Perspective { fov_y } | Projection::OffAxis { fov_y, .. } = &mut self.projection {
+            *fov_y = pose.fov_y;
         }
-        if window.is_key_down(KeyboardKey::KEY_S) {
-            self.pitch -= self.rotation_speed;
-            self.update_eye_position();
+        self.glide = None;
+        self.stop();
+    }
+
+    /// Current view as orbit parameters, worked out from eye and target so it is also
+    /// right in free-fly mode
+    pub fn orbit_view(&self) -> OrbitView {
+        let direction = self.eye - self.target;
+        let distance = direction.length().max(1e-3);
+        OrbitView {
+            yaw: direction.z.atan2(direction.x),
+            pitch: (direction.y / distance).clamp(-1.0, 1.0).asin(),
+            distance,
+            target: self.target,
+            projection: self.projection,
         }
+    }
 
-        // Zoom controls (distance from target) - arrow keys
-        if window.is_key_down(KeyboardKey::KEY_R) {
-            self.distance -= self.zoom_speed;
-            if self.distance < 0.5 {
-                self.distance = 0.5; // Prevent camera from going too close
-            }
-            self.update_eye_position();
+    /// Places the orbit camera at `view` right away
+    pub fn set_orbit_view(&mut self, view: &OrbitView) {
+        self.yaw = view.yaw;
+        self.pitch = view.pitch;
+        self.distance = view.distance;
+        self.target = view.target;
+        self.projection = view.projection;
+        self.up = Vector3::new(0.0, 1.0, 0.0);
+        self.update_eye_position();
+    }
+
+    /// Moves smoothly to `view` over `duration` seconds, in orbit mode. An off-axis view
+    /// takes this window's tile, or becomes a plain perspective without one.
+    pub fn glide_to(&mut self, view: OrbitView, duration: f32) {
+        let mut to = view;
+        if let Projection::OffAxis { fov_y, .. } = view.projection {
+            to.projection = if self.tile.is_single() {
+                Projection::Perspective { fov_y }
+            } else {
+                Projection::OffAxis { fov_y, tile: self.tile }
+            };
         }
-        if window.is_key_down(KeyboardKey::KEY_F) {
-            self.distance += self.zoom_speed;
-            self.update_eye_position();
+        self.set_mode(CameraMode::Orbit);
+        self.stop();
+        if duration <= 0.0 {
+            self.set_orbit_view(&to);
+            return;
         }
+        self.glide = Some(Glide { from: self.orbit_view(), to, elapsed: 0.0, duration });
+    }
 
-        // Pan controls (move target/center point)
-        // Calculate right and forward vectors for panning
-        let forward = Vector3::new(
-            self.target.x - self.eye.x,
-            0.0, // Keep on horizontal plane
-            self.target.z - self.eye.z,
-        );
-        let forward_len = (forward.x * forward.x + forward.z * forward.z).sqrt();
-        let forward_normalized = if forward_len > 0.0 {
-            Vector3::new(forward.x / forward_len, 0.0, forward.z / forward_len)
-        } else {
-            Vector3::new(0.0, 0.0, 1.0)
+    pub fn is_gliding(&self) -> bool {
+        self.glide.is_some()
+    }
+
+    /// Orbit -> free-fly -> follow (when there is something to follow) -> orbit
+    pub fn cycle_mode(&mut self) {
+        let next = match self.mode {
+            CameraMode::Orbit => CameraMode::FreeFly,
+            CameraMode::FreeFly if self.follow.is_some() => CameraMode::Follow,
+            _ => CameraMode::Orbit,
         };
+        self.set_mode(next);
+    }
 
-        let right = Vector3::new(
-            forward_normalized.z,
-            0.0,
-            -forward_normalized.x,
-        );
+    /// Starts following `name`; the view glides over to it
+    pub fn follow_entity(&mut self, name: &str) {
+        self.follow = Some(name.to_string());
+        self.glide = None;
+        self.set_mode(CameraMode::Follow);
+    }
 
-        // Q/E keys for horizontal panning
-        if window.is_key_down(KeyboardKey::KEY_Q) {
-            self.target.x -= right.x * self.pan_speed;
-            self.target.z -= right.z * self.pan_speed;
-            self.update_eye_position();
+    /// Follow mode: moves the target towards the followed entity's position, with
+    /// exponential smoothing that is independent of the frame rate
+    pub fn track(&mut self, position: Vector3, frame_time: f32) {
+        if self.mode != CameraMode::Follow {
+            return;
         }
-        if window.is_key_down(KeyboardKey::KEY_E) {
-            self.target.x += right.x * self.pan_speed;
-            self.target.z += right.z * self.pan_speed;
-            self.update_eye_position();
+        let blend = 1.0 - (-self.follow_smoothing * frame_time).exp();
+        self.target = self.target.lerp(position, blend);
+        self.update_eye_position();
+    }
+
+    /// World units covered by one pixel at the target distance
+    fn world_per_pixel(&self) -> f32 {
+        let visible = match self.projection {
+            Projection::Orthographic { height } => height,
+            _ => 2.0 * self.distance * (self.fov_y() * 0.5).tan(),
+        };
+        visible / self.viewport_height
+    }
+
+    /// Stops any gliding left over from inertia
+    pub fn stop(&mut self) {
+        self.yaw_velocity = 0.0;
+        self.pitch_velocity = 0.0;
+        self.roll_velocity = 0.0;
+        self.zoom_velocity = 0.0;
+        self.zoom_pending = 0.0;
+        self.pan_velocity = Vector3::zero();
+        self.fly_velocity = Vector3::zero();
+    }
+
+    /// Applies this frame's camera actions. Keys and sticks set rates, mouse drags and
+    /// wheel notches move by fixed amounts, so the result is the same at any frame rate.
+    pub fn process_input(&mut self, input: &InputMap, frame_time: f32) {
+        let dt = frame_time.max(1e-4);
+
+        // Recalling a bookmark: the glide has the camera until it arrives
+        if let Some(mut glide) = self.glide.take() {
+            glide.elapsed += dt;
+            let view = glide.from.interpolate(&glide.to, Ease::InOut.apply(glide.elapsed / glide.duration));
+            self.set_orbit_view(&view);
+            if glide.elapsed < glide.duration {
+                self.glide = Some(glide);
+            }
+            return;
         }
 
-        // Left/Right arrow keys for horizontal panning
-        if window.is_key_down(KeyboardKey::KEY_LEFT) {
-            self.target.x -= right.x * self.pan_speed;
-            self.target.z -= right.z * self.pan_speed;
-            self.update_eye_position();
+        // Projection: switch mode, widen or narrow the FOV / extents
+        if input.pressed(Action::CycleProjection) {
+            self.cycle_projection();
+            println!("proyección: {}", self.projection.name());
         }
-        if window.is_key_down(KeyboardKey::KEY_RIGHT) {
-            self.target.x += right.x * self.pan_speed;
-            self.target.z += right.z * self.pan_speed;
-            self.update_eye_position();
+        let widen = input.axis(Action::NarrowView, Action::WidenView);
+        if widen != 0.0 {
+            self.zoom_projection((1.0 + frame_time).powf(widen));
+        }
+        if input.pressed(Action::ToggleInertia) {
+            self.inertia = !self.inertia;
+            println!("inercia de la cámara: {}", if self.inertia { "sí" } else { "no" });
+        }
+        if input.pressed(Action::CycleCameraMode) {
+            self.cycle_mode();
+            println!("cámara: {}", self.mode.name());
         }
 
-        // Vertical panning
-        if window.is_key_down(KeyboardKey::KEY_R) {
-            self.target.y += self.pan_speed;
-            self.update_eye_position();
+        match self.mode {
+            CameraMode::Orbit => self.orbit_input(input, dt, true),
+            CameraMode::Follow => self.orbit_input(input, dt, false),
+            CameraMode::FreeFly => self.fly_input(input, dt),
         }
-        if window.is_key_down(KeyboardKey::KEY_F) {
-            self.target.y -= self.pan_speed;
-            self.update_eye_position();
+    }
+
+    /// Orbit and follow modes: yaw/pitch/distance around the target; panning only when
+    /// the target is not locked to an entity
+    fn orbit_input(&mut self, input: &InputMap, dt: f32, can_pan: bool) {
+        // Camera axes: right in the view plane, right/forward on the horizontal plane
+        let view_dir = (self.target - self.eye).normalized();
+        let right = view_dir.cross(self.up).normalized();
+        let view_up = right.cross(view_dir);
+        let forward = Vector3::new(view_dir.x, 0.0, view_dir.z);
+        let forward = if forward.length() > 1e-6 { forward.normalized() } else { Vector3::new(0.0, 0.0, -1.0) };
+        let flat_right = forward.cross(self.up).normalized();
+
+        // Mouse: drag to orbit; middle drag (or a modifier while dragging) pans
+        let mouse = input.mouse_delta();
+        let pan_drag = can_pan && (input.down(Action::PanDrag) || (input.down(Action::OrbitDrag) && input.down(Action::PanModifier)));
+        let orbit_drag = input.down(Action::OrbitDrag) && !pan_drag;
+
+        // Rotation (yaw and pitch)
+        let mut yaw_rate = self.rotation_speed * input.axis(Action::OrbitRight, Action::OrbitLeft);
+        let mut pitch_rate = self.rotation_speed * input.axis(Action::OrbitDown, Action::OrbitUp);
+        if orbit_drag {
+            yaw_rate += mouse.x * self.mouse_sensitivity / dt;
+            pitch_rate += mouse.y * self.mouse_sensitivity / dt;
+        }
+        let orbiting = yaw_rate != 0.0 || pitch_rate != 0.0 || orbit_drag;
+        self.yaw_velocity = self.follow_or_glide(self.yaw_velocity, yaw_rate, orbiting, dt);
+        self.pitch_velocity = self.follow_or_glide(self.pitch_velocity, pitch_rate, orbiting, dt);
+        self.yaw += self.yaw_velocity * dt;
+        self.pitch += self.pitch_velocity * dt;
+
+        // Zoom: exponential in the distance, so each notch or second scales it by the same factor.
+        // Wheel notches ease in over a few frames with inertia, at once without it.
+        let zoom_rate = -self.zoom_speed * input.axis(Action::ZoomOut, Action::ZoomIn);
+        self.zoom_velocity = self.follow_or_glide(self.zoom_velocity, zoom_rate, zoom_rate != 0.0, dt);
+        self.zoom_pending -= self.wheel_step * input.impulse_axis(Action::ZoomOut, Action::ZoomIn);
+        let wheel_zoom = if self.inertia { self.zoom_pending * (1.0 - (-2.0 * self.damping * dt).exp()) } else { self.zoom_pending };
+        self.zoom_pending -= wheel_zoom;
+        self.distance = (self.distance * (self.zoom_velocity * dt + wheel_zoom).exp()).max(0.5); // Prevent camera from going too close
+
+        // Pan (move target/center point): keys on the horizontal plane and vertically,
+        // mouse in the view plane so the point under the cursor follows it
+        let (pan_x, pan_y) = if can_pan {
+            (input.axis(Action::PanLeft, Action::PanRight), input.axis(Action::PanDown, Action::PanUp))
+        } else {
+            (0.0, 0.0)
+        };
+        let mut pan_rate = (flat_right * pan_x + self.up * pan_y) * (self.pan_speed * self.distance);
+        if pan_drag {
+            let per_pixel = self.world_per_pixel();
+            pan_rate += (right * -mouse.x + view_up * mouse.y) * (per_pixel / dt);
         }
+        let panning = pan_x != 0.0 || pan_y != 0.0 || pan_drag;
+        self.pan_velocity = if panning {
+            pan_rate
+        } else if self.inertia {
+            self.pan_velocity * (-self.damping * dt).exp()
+        } else {
+            Vector3::zero()
+        };
+        self.target += self.pan_velocity * dt;
+
+        self.update_eye_position();
     }
-}
\ No newline at end of file
+
+    /// Free-fly: look with mouse drag or the right stick (yaw about the camera's up, pitch
+    /// about its right, roll about its view axis) and move along the camera axes
+    fn fly_input(&mut self, input: &InputMap, dt: f32) {
+        let mouse = input.mouse_delta();
+        let looking = input.down(Action::LookDrag);
+        let mut yaw_rate = self.rotation_speed * input.axis(Action::LookRight, Action::LookLeft);
+        let mut pitch_rate = self.rotation_speed * input.axis(Action::LookDown, Action::LookUp);
+        let roll_rate = self.rotation_speed * input.axis(Action::RollRight, Action::RollLeft);
+        if looking {
+            yaw_rate -= mouse.x * self.mouse_sensitivity / dt;
+            pitch_rate -= mouse.y * self.mouse_sensitivity / dt;
+        }
+        let turning = yaw_rate != 0.0 || pitch_rate != 0.0 || looking;
+        self.yaw_velocity = self.follow_or_glide(self.yaw_velocity, yaw_rate, turning, dt);
+        self.pitch_velocity = self.follow_or_glide(self.pitch_velocity, pitch_rate, turning, dt);
+        self.roll_velocity = self.follow_or_glide(self.roll_velocity, roll_rate, roll_rate != 0.0, dt);
+        self.orientation = (self.orientation
+            * Quat::from_axis_angle(Vector3::new(0.0, 1.0, 0.0), self.yaw_velocity * dt)
+            * Quat::from_axis_angle(Vector3::new(1.0, 0.0, 0.0), self.pitch_velocity * dt)
+            * Quat::from_axis_angle(Vector3::new(0.0, 0.0, 1.0), self.roll_velocity * dt))
+            .normalized();
+
+        let forward = self.orientation.rotate(Vector3::new(0.0, 0.0, -1.0));
+        let right = self.orientation.rotate(Vector3::new(1.0, 0.0, 0.0));
+        let up = self.orientation.rotate(Vector3::new(0.0, 1.0, 0.0));
+
+        let direction = forward * input.axis(Action::FlyBack, Action::FlyForward)
+            + right * input.axis(Action::FlyLeft, Action::FlyRight)
+            + up * input.axis(Action::FlyDown, Action::FlyUp);
+        let moving = direction.length() > 0.0;
+        let speed = if input.down(Action::FlyBoost) { self.fly_speed * self.boost } else { self.fly_speed };
+        self.fly_velocity = if moving {
+            direction * speed
+        } else if self.inertia {
+            self.fly_velocity * (-self.damping * dt).exp()
+        } else {
+            Vector3::zero()
+        };
+        self.eye += self.fly_velocity * dt;
+
+        // The target stays ahead at the focus distance, so orbiting resumes around it
+        self.target = self.eye + forward * self.distance;
+        self.up = up;
+    }
+
+    /// While input drives a channel its velocity is the input rate; once released it
+    /// decays exponentially with inertia, or stops at once without it
+    fn follow_or_glide(&self, velocity: f32, rate: f32, active: bool, dt: f32) -> f32 {
+        if active {
+            rate
+        } else if self.inertia {
+            let v = velocity * (-self.damping * dt).exp();
+            if v.abs() < 1e-4 { 0.0 } else { v }
+        } else {
+            0.0
+        }
+    }
+}
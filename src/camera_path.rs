@@ -0,0 +1,237 @@
+#![allow(dead_code)]
+
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::ops::{Add, Mul, Sub};
+use std::path::Path;
+
+type Vec3 = [f32; 3];
+
+fn v3(v: Vec3) -> Vector3 {
+    Vector3::new(v[0], v[1], v[2])
+}
+
+fn arr(v: Vector3) -> Vec3 {
+    [v.x, v.y, v.z]
+}
+
+/// How the pose moves between keyframes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Interpolation {
+    /// Passes through every keyframe with tangents from the neighbours (time-aware,
+    /// so uneven spacing does not make the camera surge)
+    #[default]
+    CatmullRom,
+    /// Cubic Bézier segments; each keyframe's `handle` sets its tangent, keyframes
+    /// without one get the Catmull-Rom tangent
+    Bezier,
+}
+
+/// Remaps the time within a segment, e.g. to slow down into a keyframe
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Ease {
+    #[default]
+    Linear,
+    In,
+    Out,
+    InOut,
+}
+
+impl Ease {
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Ease::Linear => t,
+            Ease::In => t * t * t,
+            Ease::Out => 1.0 - (1.0 - t).powi(3),
+            Ease::InOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// Camera state at one instant of a path
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CameraPose {
+    pub eye: Vector3,
+    pub target: Vector3,
+    pub fov_y: f32, // radians
+}
+
+impl CameraPose {
+    pub fn interpolate(&self, other: &CameraPose, t: f32) -> CameraPose {
+        CameraPose {
+            eye: self.eye.lerp(other.eye, t),
+            target: self.target.lerp(other.target, t),
+            fov_y: self.fov_y + (other.fov_y - self.fov_y) * t,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Keyframe {
+    pub time: f32, // seconds from the start of the path
+    pub pose: CameraPose,
+    pub ease: Option<Ease>,             // for the segment that starts here; the path's when None
+    pub handle: Option<(Vector3, Vector3)>, // Bézier tangent offsets of (eye, target), outgoing side
+}
+
+/// A scripted camera flight: keyframes sorted by time and a spline through them
+#[derive(Clone, Debug, Default)]
+pub struct CameraPath {
+    pub keyframes: Vec<Keyframe>,
+    pub interpolation: Interpolation,
+    pub ease: Ease,
+    pub looping: bool,
+}
+
+/// Cubic Hermite on [0, 1] with tangents already scaled to the segment length
+fn hermite<T>(p0: T, m0: T, p1: T, m1: T, s: f32) -> T
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>,
+{
+    let (s2, s3) = (s * s, s * s * s);
+    p0 * (2.0 * s3 - 3.0 * s2 + 1.0) + m0 * (s3 - 2.0 * s2 + s) + p1 * (3.0 * s2 - 2.0 * s3) + m1 * (s3 - s2)
+}
+
+impl CameraPath {
+    pub fn new(interpolation: Interpolation) -> Self {
+        CameraPath { interpolation, ..CameraPath::default() }
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |k| k.time)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    /// Adds a keyframe `spacing` seconds after the last one (at 0 for the first)
+    pub fn record(&mut self, pose: CameraPose, spacing: f32) -> f32 {
+        let time = self.keyframes.last().map_or(0.0, |k| k.time + spacing);
+        self.keyframes.push(Keyframe { time, pose, ease: None, handle: None });
+        time
+    }
+
+    /// Catmull-Rom tangent at keyframe `i` in units per second, one-sided at the ends
+    fn tangent<T>(&self, i: usize, get: impl Fn(&CameraPose) -> T) -> T
+    where
+        T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>,
+    {
+        let k = &self.keyframes;
+        let (a, b) = (i.saturating_sub(1), (i + 1).min(k.len() - 1));
+        let dt = k[b].time - k[a].time;
+        if dt <= 1e-6 {
+            return get(&k[i].pose) * 0.0;
+        }
+        (get(&k[b].pose) - get(&k[a].pose)) * (1.0 / dt)
+    }
+
+    /// Pose at `time`; before the first keyframe and after the last the ends are held
+    pub fn sample(&self, time: f32) -> Option<CameraPose> {
+        let k = &self.keyframes;
+        let first = k.first()?;
+        let mut time = time;
+        if self.looping && self.duration() > 0.0 {
+            time = time.rem_euclid(self.duration());
+        }
+        if k.len() == 1 || time <= first.time {
+            return Some(first.pose);
+        }
+        let i = match k.iter().rposition(|key| key.time <= time) {
+            Some(i) if i + 1 < k.len() => i,
+            _ => return Some(k[k.len() - 1].pose),
+        };
+        let (a, b) = (&k[i], &k[i + 1]);
+        let h = b.time - a.time;
+        if h <= 1e-6 {
+            return Some(b.pose);
+        }
+        let s = a.ease.unwrap_or(self.ease).apply((time - a.time) / h);
+
+        // Tangents scaled to the segment length (Hermite form); Bézier handles are a third
+        // of the Hermite tangent, so a handle of m·h/3 reproduces the Catmull-Rom curve
+        let eye_tangent = |j: usize| match (self.interpolation, k[j].handle) {
+            (Interpolation::Bezier, Some((eye, _))) => eye * 3.0,
+            _ => self.tangent(j, |p| p.eye) * h,
+        };
+        let target_tangent = |j: usize| match (self.interpolation, k[j].handle) {
+            (Interpolation::Bezier, Some((_, target))) => target * 3.0,
+            _ => self.tangent(j, |p| p.target) * h,
+        };
+        Some(CameraPose {
+            eye: hermite(a.pose.eye, eye_tangent(i), b.pose.eye, eye_tangent(i + 1), s),
+            target: hermite(a.pose.target, target_tangent(i), b.pose.target, target_tangent(i + 1), s),
+            fov_y: hermite(a.pose.fov_y, self.tangent(i, |p| p.fov_y) * h, b.pose.fov_y, self.tangent(i + 1, |p| p.fov_y) * h, s),
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<CameraPath, String> {
+        let source = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let desc: PathDesc = ron::Options::default()
+            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+            .from_str(&source)
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+        let mut keyframes: Vec<Keyframe> = desc.keyframes.iter().map(|k| Keyframe {
+            time: k.time,
+            pose: CameraPose { eye: v3(k.eye), target: v3(k.target), fov_y: k.fov_y.to_radians() },
+            ease: k.ease,
+            handle: k.handle.map(|(eye, target)| (v3(eye), v3(target))),
+        }).collect();
+        if keyframes.iter().any(|k| !k.time.is_finite() || !k.pose.fov_y.is_finite()) {
+            return Err(format!("{}: tiempo o fov inválido en un fotograma clave", path.display()));
+        }
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Ok(CameraPath { keyframes, interpolation: desc.interpolation, ease: desc.ease, looping: desc.looping })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let desc = PathDesc {
+            interpolation: self.interpolation,
+            ease: self.ease,
+            looping: self.looping,
+            keyframes: self.keyframes.iter().map(|k| KeyframeDesc {
+                time: k.time,
+                eye: arr(k.pose.eye),
+                target: arr(k.pose.target),
+                fov_y: k.pose.fov_y.to_degrees(),
+                ease: k.ease,
+                handle: k.handle.map(|(eye, target)| (arr(eye), arr(target))),
+            }).collect(),
+        };
+        let text = ron::ser::to_string_pretty(&desc, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())?;
+        std::fs::write(path, text + "\n").map_err(|e| format!("{}: {e}", path.display()))
+    }
+}
+
+// --- File format (RON); FOV in degrees for readability ---
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PathDesc {
+    #[serde(default)]
+    interpolation: Interpolation,
+    #[serde(default)]
+    ease: Ease,
+    #[serde(default)]
+    looping: bool,
+    keyframes: Vec<KeyframeDesc>,
+}
+
+fn default_fov() -> f32 { 60.0 }
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KeyframeDesc {
+    time: f32,
+    eye: Vec3,
+    #[serde(default)]
+    target: Vec3,
+    #[serde(default = "default_fov")]
+    fov_y: f32, // degrees
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ease: Option<Ease>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    handle: Option<(Vec3, Vec3)>,
+}
@@ -0,0 +1,171 @@
+#![allow(dead_code)]
+
+use raylib::prelude::*;
+use crate::input::{Action, InputMap};
+
+/// Speed multipliers cycled with +/-; the sign is kept separately so reverse
+/// playback walks the same ladder.
+const SPEEDS: [f32; 11] = [0.0625, 0.125, 0.25, 0.5, 1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0];
+const NORMAL_SPEED: usize = 4;
+
+/// Simulation time, decoupled from wall-clock time. Everything animated (uniforms,
+/// orbits, ephemeris, N-body) reads `time` and `dt` from here.
+pub struct SimClock {
+    pub time: f32,               // simulation seconds
+    pub dt: f32,                 // simulation seconds advanced by the last `advance`
+    pub paused: bool,
+    pub reversed: bool,
+    pub fixed_step: Option<f32>, // deterministic mode: every frame advances exactly this much (× speed)
+    pub step_size: f32,          // single-step increment while paused
+    speed_index: usize,
+    pending_steps: i32,          // single steps requested since the last frame (negative = back)
+    jump_entry: Option<String>,  // digits typed after J, committed with Enter
+}
+
+impl SimClock {
+    pub fn new(start: f32) -> Self {
+        SimClock {
+            time: start,
+            dt: 0.0,
+            paused: false,
+            reversed: false,
+            fixed_step: None,
+            step_size: 1.0 / 30.0,
+            speed_index: NORMAL_SPEED,
+            pending_steps: 0,
+            jump_entry: None,
+        }
+    }
+
+    pub fn with_fixed_step(mut self, step: f32) -> Self {
+        self.fixed_step = Some(step);
+        self.step_size = step;
+        self
+    }
+
+    /// Signed speed multiplier
+    pub fn speed(&self) -> f32 {
+        let speed = SPEEDS[self.speed_index];
+        if self.reversed { -speed } else { speed }
+    }
+
+    pub fn faster(&mut self) {
+        self.speed_index = (self.speed_index + 1).min(SPEEDS.len() - 1);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed_index = self.speed_index.saturating_sub(1);
+    }
+
+    pub fn reset_speed(&mut self) {
+        self.speed_index = NORMAL_SPEED;
+        self.reversed = false;
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    pub fn reverse(&mut self) {
+        self.reversed = !self.reversed;
+    }
+
+    /// Queues one step of `step_size` (backwards if `direction` < 0); pauses the clock
+    pub fn step(&mut self, direction: i32) {
+        self.paused = true;
+        self.pending_steps += direction.signum();
+    }
+
+    pub fn jump_to(&mut self, time: f32) {
+        self.time = time;
+    }
+
+    /// Advances by one rendered frame that took `frame_time` wall seconds and returns
+    /// the simulation delta. In fixed-step mode the wall time is ignored.
+    pub fn advance(&mut self, frame_time: f32) -> f32 {
+        let dt = if self.paused {
+            self.pending_steps as f32 * self.step_size
+        } else {
+            self.fixed_step.unwrap_or(frame_time) * self.speed()
+        };
+        self.pending_steps = 0;
+        self.time += dt;
+        self.dt = dt;
+        dt
+    }
+
+    /// True while a `JumpTo` time is being typed; digits belong to the entry then
+    pub fn is_typing(&self) -> bool {
+        self.jump_entry.is_some()
+    }
+
+    /// Pause, single steps, speed, reverse, fixed step on/off and jumps, from the input
+    /// map. `JumpTo` then digits and Enter jumps to a typed time; while typing, the keys
+    /// go to the entry instead of the actions.
+    pub fn process_input(&mut self, window: &mut RaylibHandle, input: &InputMap) {
+        if let Some(entry) = &mut self.jump_entry {
+            while let Some(c) = window.get_char_pressed() {
+                if c.is_ascii_digit() || c == '.' || c == '-' {
+                    entry.push(c);
+                }
+            }
+            if window.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+                entry.pop();
+            }
+            if window.is_key_pressed(KeyboardKey::KEY_ENTER) {
+                match entry.parse::<f32>() {
+                    Ok(t) => {
+                        self.jump_to(t);
+                        println!("reloj: t = {t}");
+                    }
+                    Err(_) => println!("reloj: tiempo inválido '{entry}'"),
+                }
+                self.jump_entry = None;
+            } else if input.pressed(Action::JumpTo) {
+                self.jump_entry = None; // JumpTo again cancels
+            }
+            return;
+        }
+
+        let before = (self.paused, self.speed());
+        if input.pressed(Action::Pause) { self.toggle_pause(); }
+        if input.pressed(Action::StepForward) { self.step(1); }
+        if input.pressed(Action::StepBack) { self.step(-1); }
+        if input.pressed(Action::Faster) { self.faster(); }
+        if input.pressed(Action::Slower) { self.slower(); }
+        if input.pressed(Action::Reverse) { self.reverse(); }
+        if input.pressed(Action::ResetSpeed) { self.reset_speed(); }
+        if input.pressed(Action::ToggleFixedStep) {
+            self.fixed_step = match self.fixed_step {
+                Some(_) => None,
+                None => Some(self.step_size),
+            };
+            println!("reloj: {self}");
+        }
+        if input.pressed(Action::JumpStart) { self.jump_to(0.0); }
+        if input.pressed(Action::JumpForward) { self.jump_to(self.time + 60.0); }
+        if input.pressed(Action::JumpBack) { self.jump_to(self.time - 60.0); }
+        if input.pressed(Action::JumpTo) {
+            self.jump_entry = Some(String::new());
+            // Drop the key's own character from the queue
+            while window.get_char_pressed().is_some() {}
+            println!("reloj: escribe el tiempo y pulsa Enter");
+        }
+        if before != (self.paused, self.speed()) {
+            println!("reloj: {self}");
+        }
+    }
+}
+
+impl std::fmt::Display for SimClock {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "t = {:.2} s, x{}", self.time, self.speed())?;
+        if self.paused {
+            write!(f, " (pausa)")?;
+        }
+        if let Some(step) = self.fixed_step {
+            write!(f, " [paso fijo {step} s]")?;
+        }
+        Ok(())
+    }
+}
@@ -1,12 +1,79 @@
 use raylib::prelude::*;
 
-use crate::VertexShader;
+use crate::{FragmentShader, VertexShader};
+use crate::ephemeris;
+use crate::light::Light;
+use crate::orbit::Orbit;
+use crate::ring::Rings;
+use crate::transform::{Quat, Transform};
+
+/// Cached world matrix of a scene-graph node, rebuilt by `Scene::update_transforms`
+#[derive(Clone, Copy, Debug)]
+pub struct WorldTransform {
+    pub matrix: Matrix,
+    pub dirty: bool,                           // forces a rebuild on the next update
+    pub(crate) local: Option<(Transform, f32)>, // local transform and spin angle the matrix was built from
+    pub(crate) parent: Option<usize>,          // parent index it was built against
+}
+
+impl Default for WorldTransform {
+    fn default() -> Self {
+        WorldTransform { matrix: Matrix::identity(), dirty: true, local: None, parent: None }
+    }
+}
+
 pub struct Entity {
-    pub name: &'static str,
-    pub translation: Vector3,
-    pub rotation: Vector3,
-    pub scale: f32,
+    pub name: String,
+    pub parent: Option<String>,   // scene-graph parent; `transform` is relative to it
+    pub transform: Transform,
+    pub spin_angle: f32,          // current angle about the local Y axis (spin + tangent facing), set by update_motion
+    pub world: WorldTransform,
     pub vertices: Vec<Vector3>,
     pub vshader: VertexShader,
+    pub fshader: FragmentShader,
+    pub light: Option<Light>,     // emitter carried by the entity, positioned at its world position
+    pub casts_shadow: bool,       // rendered into shadow maps (off for emitters, which enclose their light)
+    pub rings: Option<Rings>,     // ring system drawn around the entity
+    pub orbit: Option<Orbit>,     // Keplerian path around the parent; overrides translation every frame
+    pub spin: f32,                // axial spin about the local Y axis, radians per second
     pub face_tangent: bool,       // if true, add tangent-facing yaw from orbital motion
-}
\ No newline at end of file
+    pub mass: f32,                // gravitational parameter G·m for the N-body mode; 0 = not simulated
+    pub simulated: bool,          // position owned by the N-body simulation instead of the orbit
+    pub ephemeris: Option<ephemeris::Body>, // real solar-system body placed from the ephemeris by date
+}
+
+impl Entity {
+    /// Radius of the bounding sphere around the entity's origin, in world units
+    pub fn bounding_radius(&self) -> f32 {
+        let scale = self.world_scale();
+        self.vertices.iter().fold(0.0f32, |m, v| m.max(v.length())) * scale.x.max(scale.y).max(scale.z)
+    }
+
+    /// Local transform including the spin angle, as used for the world matrix
+    pub fn local_transform(&self) -> Transform {
+        let spin = Quat::from_axis_angle(Vector3::new(0.0, 1.0, 0.0), self.spin_angle);
+        Transform { rotation: self.transform.rotation * spin, ..self.transform }
+    }
+
+    /// Origin of the entity in world space, from the cached world matrix
+    pub fn world_position(&self) -> Vector3 {
+        let m = &self.world.matrix;
+        Vector3::new(m.m12, m.m13, m.m14)
+    }
+
+    /// Per-axis scale accumulated down the scene graph (lengths of the matrix columns)
+    pub fn world_scale(&self) -> Vector3 {
+        let m = &self.world.matrix;
+        Vector3::new(
+            Vector3::new(m.m0, m.m1, m.m2).length(),
+            Vector3::new(m.m4, m.m5, m.m6).length(),
+            Vector3::new(m.m8, m.m9, m.m10).length(),
+        )
+    }
+
+    /// Placed directly in world space (by the N-body simulation or the ephemeris),
+    /// so the parent's transform does not apply
+    pub fn is_free(&self) -> bool {
+        self.simulated || self.ephemeris.is_some()
+    }
+}
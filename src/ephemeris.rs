@@ -0,0 +1,475 @@
+#![allow(dead_code)]
+
+use raylib::prelude::*;
+use std::f64::consts::PI;
+use crate::orbit::solve_kepler;
+
+/// Julian day of the J2000.0 epoch (2000-01-01 12:00 TT)
+pub const J2000: f64 = 2451545.0;
+const UNIX_EPOCH_JD: f64 = 2440587.5;
+const EARTH_MOON_MASS_RATIO: f64 = 81.3005;
+const EARTH_RADIUS_AU: f64 = 6371.0 / 149_597_870.7;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Body {
+    Sun,
+    Mercury,
+    Venus,
+    Earth,
+    Mars,
+    Jupiter,
+    Saturn,
+    Uranus,
+    Neptune,
+    Moon,
+}
+
+impl Body {
+    pub const ALL: [Body; 10] = [
+        Body::Sun, Body::Mercury, Body::Venus, Body::Earth, Body::Mars,
+        Body::Jupiter, Body::Saturn, Body::Uranus, Body::Neptune, Body::Moon,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Body::Sun => "sun",
+            Body::Mercury => "mercury",
+            Body::Venus => "venus",
+            Body::Earth => "earth",
+            Body::Mars => "mars",
+            Body::Jupiter => "jupiter",
+            Body::Saturn => "saturn",
+            Body::Uranus => "uranus",
+            Body::Neptune => "neptune",
+            Body::Moon => "moon",
+        }
+    }
+}
+
+/// Mean elements at J2000 and their rates per Julian century: a (AU), e, I, L, ϖ, Ω (degrees).
+/// Standish, "Keplerian Elements for Approximate Positions of the Major Planets" (JPL),
+/// table 1, valid 1800–2050 to a few arcminutes; usable well outside that range for display.
+struct Elements {
+    a: [f64; 2],
+    e: [f64; 2],
+    inclination: [f64; 2],
+    mean_longitude: [f64; 2],
+    perihelion_longitude: [f64; 2],
+    node_longitude: [f64; 2],
+}
+
+const MERCURY: Elements = Elements {
+    a: [0.38709927, 0.00000037],
+    e: [0.20563593, 0.00001906],
+    inclination: [7.00497902, -0.00594749],
+    mean_longitude: [252.25032350, 149472.67411175],
+    perihelion_longitude: [77.45779628, 0.16047689],
+    node_longitude: [48.33076593, -0.12534081],
+};
+const VENUS: Elements = Elements {
+    a: [0.72333566, 0.00000390],
+    e: [0.00677672, -0.00004107],
+    inclination: [3.39467605, -0.00078890],
+    mean_longitude: [181.97909950, 58517.81538729],
+    perihelion_longitude: [131.60246718, 0.00268329],
+    node_longitude: [76.67984255, -0.27769418],
+};
+// Earth-Moon barycentre
+const EARTH_MOON: Elements = Elements {
+    a: [1.00000261, 0.00000562],
+    e: [0.01671123, -0.00004392],
+    inclination: [-0.00001531, -0.01294668],
+    mean_longitude: [100.46457166, 35999.37244981],
+    perihelion_longitude: [102.93768193, 0.32327364],
+    node_longitude: [0.0, 0.0],
+};
+const MARS: Elements = Elements {
+    a: [1.52371034, 0.00001847],
+    e: [0.09339410, 0.00007882],
+    inclination: [1.84969142, -0.00813131],
+    mean_longitude: [-4.55343205, 19140.30268499],
+    perihelion_longitude: [-23.94362959, 0.44441088],
+    node_longitude: [49.55953891, -0.29257343],
+};
+const JUPITER: Elements = Elements {
+    a: [5.20288700, -0.00011607],
+    e: [0.04838624, -0.00013253],
+    inclination: [1.30439695, -0.00183714],
+    mean_longitude: [34.39644051, 3034.74612775],
+    perihelion_longitude: [14.72847983, 0.21252668],
+    node_longitude: [100.47390909, 0.20469106],
+};
+const SATURN: Elements = Elements {
+    a: [9.53667594, -0.00125060],
+    e: [0.05386179, -0.00050991],
+    inclination: [2.48599187, 0.00193609],
+    mean_longitude: [49.95424423, 1222.49362201],
+    perihelion_longitude: [92.59887831, -0.41897216],
+    node_longitude: [113.66242448, -0.28867794],
+};
+const URANUS: Elements = Elements {
+    a: [19.18916464, -0.00196176],
+    e: [0.04725744, -0.00004397],
+    inclination: [0.77263783, -0.00242939],
+    mean_longitude: [313.23810451, 428.48202785],
+    perihelion_longitude: [170.95427630, 0.40805281],
+    node_longitude: [74.01692503, 0.04240589],
+};
+const NEPTUNE: Elements = Elements {
+    a: [30.06992276, 0.00026291],
+    e: [0.00859048, 0.00005105],
+    inclination: [1.77004347, 0.00035372],
+    mean_longitude: [-55.12002969, 218.45945325],
+    perihelion_longitude: [44.96476227, -0.32241464],
+    node_longitude: [131.78422574, -0.00508664],
+};
+
+/// Ecliptic (x, y, z) with z towards the north ecliptic pole -> scene (Y up)
+fn to_scene(x: f64, y: f64, z: f64) -> Vector3 {
+    Vector3::new(x as f32, z as f32, -y as f32)
+}
+
+fn elements_position(el: &Elements, jd: f64) -> (f64, f64, f64) {
+    let t = (jd - J2000) / 36525.0;
+    let at = |v: [f64; 2]| v[0] + v[1] * t;
+
+    let a = at(el.a);
+    let e = at(el.e);
+    let i = at(el.inclination).to_radians();
+    let l = at(el.mean_longitude);
+    let peri = at(el.perihelion_longitude);
+    let node = at(el.node_longitude);
+    let omega = (peri - node).to_radians(); // argument of perihelion
+    let node = node.to_radians();
+    let m = (l - peri).to_radians().rem_euclid(2.0 * PI);
+
+    let ecc = solve_kepler(m as f32, e as f32) as f64;
+    // Perihelion-frame coordinates
+    let xp = a * (ecc.cos() - e);
+    let yp = a * (1.0 - e * e).sqrt() * ecc.sin();
+
+    let (so, co) = omega.sin_cos();
+    let (sn, cn) = node.sin_cos();
+    let (si, ci) = i.sin_cos();
+    let x = (co * cn - so * sn * ci) * xp + (-so * cn - co * sn * ci) * yp;
+    let y = (co * sn + so * cn * ci) * xp + (-so * sn + co * cn * ci) * yp;
+    let z = (so * si) * xp + (co * si) * yp;
+    (x, y, z)
+}
+
+/// Geocentric ecliptic position of the Moon in AU: mean orbit plus the largest
+/// periodic terms (evection, variation, yearly equation, ...), good to a fraction of a degree.
+/// After P. Schlyter, "How to compute planetary positions".
+fn moon_geocentric(jd: f64) -> (f64, f64, f64) {
+    let d = jd - 2451543.5;
+    let deg = |x: f64| x.to_radians();
+
+    let node = 125.1228 - 0.0529538083 * d;
+    let inclination = 5.1454;
+    let w = 318.0634 + 0.1643573223 * d;
+    let a = 60.2666; // Earth radii
+    let e = 0.054900;
+    let mm = 115.3654 + 13.0649929509 * d;
+
+    // Sun's mean anomaly and longitude, for the perturbations
+    let ms = 356.0470 + 0.9856002585 * d;
+    let ws = 282.9404 + 4.70935e-5 * d;
+    let ls = ms + ws;
+    let lm = node + w + mm;
+    let dd = lm - ls; // mean elongation
+    let f = lm - node; // argument of latitude
+
+    let ecc = solve_kepler(deg(mm).rem_euclid(2.0 * PI) as f32, e as f32) as f64;
+    let xv = a * (ecc.cos() - e);
+    let yv = a * (1.0 - e * e).sqrt() * ecc.sin();
+    let v = yv.atan2(xv);
+    let r = (xv * xv + yv * yv).sqrt();
+
+    let (sn, cn) = deg(node).sin_cos();
+    let (svw, cvw) = (v + deg(w)).sin_cos();
+    let ci = deg(inclination).cos();
+    let si = deg(inclination).sin();
+    let xh = r * (cn * cvw - sn * svw * ci);
+    let yh = r * (sn * cvw + cn * svw * ci);
+    let zh = r * (svw * si);
+
+    let mut lon = yh.atan2(xh).to_degrees();
+    let mut lat = zh.atan2((xh * xh + yh * yh).sqrt()).to_degrees();
+    let mut dist = r;
+
+    let s = |x: f64| deg(x).sin();
+    let c = |x: f64| deg(x).cos();
+    lon += -1.274 * s(mm - 2.0 * dd)
+        + 0.658 * s(2.0 * dd)
+        - 0.186 * s(ms)
+        - 0.059 * s(2.0 * mm - 2.0 * dd)
+        - 0.057 * s(mm - 2.0 * dd + ms)
+        + 0.053 * s(mm + 2.0 * dd)
+        + 0.046 * s(2.0 * dd - ms)
+        + 0.041 * s(mm - ms)
+        - 0.035 * s(dd)
+        - 0.031 * s(mm + ms)
+        - 0.015 * s(2.0 * f - 2.0 * dd)
+        + 0.011 * s(mm - 4.0 * dd);
+    lat += -0.173 * s(f - 2.0 * dd)
+        - 0.055 * s(mm - f - 2.0 * dd)
+        - 0.046 * s(mm + f - 2.0 * dd)
+        + 0.033 * s(f + 2.0 * dd)
+        + 0.017 * s(2.0 * mm + f);
+    dist += -0.58 * c(mm - 2.0 * dd) - 0.46 * c(2.0 * dd);
+
+    // Schlyter's angles are referred to the equinox of date; precess back to J2000
+    lon -= 3.82394e-5 * d;
+
+    let dist = dist * EARTH_RADIUS_AU;
+    let (slon, clon) = deg(lon).sin_cos();
+    let (slat, clat) = deg(lat).sin_cos();
+    (dist * clat * clon, dist * clat * slon, dist * slat)
+}
+
+/// Heliocentric ecliptic (J2000) position of `body` in AU, in scene axes (Y = ecliptic north)
+pub fn heliocentric(body: Body, jd: f64) -> Vector3 {
+    let (x, y, z) = match body {
+        Body::Sun => (0.0, 0.0, 0.0),
+        Body::Mercury => elements_position(&MERCURY, jd),
+        Body::Venus => elements_position(&VENUS, jd),
+        Body::Earth | Body::Moon => {
+            let (bx, by, bz) = elements_position(&EARTH_MOON, jd);
+            let (mx, my, mz) = moon_geocentric(jd);
+            // The table gives the barycentre; Earth sits 1/82 of the way towards the Moon
+            let k = 1.0 / (1.0 + EARTH_MOON_MASS_RATIO);
+            let (ex, ey, ez) = (bx - mx * k, by - my * k, bz - mz * k);
+            if body == Body::Earth { (ex, ey, ez) } else { (ex + mx, ey + my, ez + mz) }
+        }
+        Body::Mars => elements_position(&MARS, jd),
+        Body::Jupiter => elements_position(&JUPITER, jd),
+        Body::Saturn => elements_position(&SATURN, jd),
+        Body::Uranus => elements_position(&URANUS, jd),
+        Body::Neptune => elements_position(&NEPTUNE, jd),
+    };
+    to_scene(x, y, z)
+}
+
+/// Geocentric position of the Moon in AU, in scene axes
+pub fn moon_offset(jd: f64) -> Vector3 {
+    let (x, y, z) = moon_geocentric(jd);
+    to_scene(x, y, z)
+}
+
+/// Maps true distances to something that fits on screen: directions are kept and the
+/// radius is compressed (√AU), so Mercury and Neptune are both visible around the sun.
+#[derive(Clone, Copy, Debug)]
+pub struct DisplayScale {
+    pub offset: f32,        // scene units added to every non-zero radius (clears the sun)
+    pub factor: f32,        // scene units per √AU
+    pub moon_distance: f32, // the Moon is drawn at this fixed distance from the Earth
+}
+
+impl Default for DisplayScale {
+    fn default() -> Self {
+        DisplayScale { offset: 4.0, factor: 5.0, moon_distance: 1.2 }
+    }
+}
+
+impl DisplayScale {
+    pub fn position(&self, body: Body, jd: f64) -> Vector3 {
+        match body {
+            Body::Sun => Vector3::zero(),
+            Body::Moon => {
+                let earth = self.position(Body::Earth, jd);
+                earth + moon_offset(jd).normalized() * self.moon_distance
+            }
+            _ => {
+                let p = heliocentric(body, jd);
+                let r = p.length();
+                p.normalized() * (self.offset + self.factor * r.sqrt())
+            }
+        }
+    }
+}
+
+/// Julian day for a Gregorian calendar date and UTC time (Meeus, ch. 7)
+pub fn julian_day(year: i32, month: u32, day: u32, hour: u32, minute: u32, second: f64) -> f64 {
+    let (mut y, mut m) = (year as f64, month as f64);
+    if month <= 2 {
+        y -= 1.0;
+        m += 12.0;
+    }
+    let a = (y / 100.0).floor();
+    let b = 2.0 - a + (a / 4.0).floor();
+    let day_fraction = (hour as f64 + minute as f64 / 60.0 + second / 3600.0) / 24.0;
+    (365.25 * (y + 4716.0)).floor() + (30.6001 * (m + 1.0)).floor() + day as f64 + day_fraction + b - 1524.5
+}
+
+/// Length of `month` (1-12) in the proleptic Gregorian calendar
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Gregorian calendar date and time of a Julian day (Meeus, ch. 7)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CalendarDate {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: f64,
+}
+
+impl CalendarDate {
+    pub fn from_julian_day(jd: f64) -> Self {
+        let jd = jd + 0.5;
+        let z = jd.floor();
+        let f = jd - z;
+        let alpha = ((z - 1867216.25) / 36524.25).floor();
+        let a = z + 1.0 + alpha - (alpha / 4.0).floor();
+        let b = a + 1524.0;
+        let c = ((b - 122.1) / 365.25).floor();
+        let d = (365.25 * c).floor();
+        let e = ((b - d) / 30.6001).floor();
+
+        let day = (b - d - (30.6001 * e).floor()) as u32;
+        let month = if e < 14.0 { e - 1.0 } else { e - 13.0 } as u32;
+        let year = if month > 2 { c - 4716.0 } else { c - 4715.0 } as i32;
+        let seconds = f * 86400.0;
+        let hour = (seconds / 3600.0).floor() as u32;
+        let minute = ((seconds - hour as f64 * 3600.0) / 60.0).floor() as u32;
+        let second = seconds - hour as f64 * 3600.0 - minute as f64 * 60.0;
+        CalendarDate { year, month, day, hour, minute, second }
+    }
+
+    pub fn julian_day(&self) -> f64 {
+        julian_day(self.year, self.month, self.day, self.hour, self.minute, self.second)
+    }
+
+    /// Parses `YYYY-MM-DD`, `YYYY-MM-DDTHH:MM` or `YYYY-MM-DDTHH:MM:SS` (a space also
+    /// separates date and time). Negative years are allowed.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (date, time) = match text.find(['T', ' ']) {
+            Some(i) => (&text[..i], Some(&text[i + 1..])),
+            None => (text, None),
+        };
+        let (sign, date) = match date.strip_prefix('-') {
+            Some(rest) => (-1, rest),
+            None => (1, date),
+        };
+        let mut parts = date.split('-');
+        let year: i32 = parts.next()?.parse().ok()?;
+        let month: u32 = parts.next()?.parse().ok()?;
+        let day: u32 = parts.next()?.parse().ok()?;
+        if parts.next().is_some() || !(1..=12).contains(&month) || !(1..=days_in_month(sign * year, month)).contains(&day) {
+            return None;
+        }
+
+        let (mut hour, mut minute, mut second) = (0, 0, 0.0);
+        if let Some(time) = time {
+            let mut parts = time.split(':');
+            hour = parts.next()?.parse().ok()?;
+            minute = parts.next()?.parse().ok()?;
+            if let Some(s) = parts.next() {
+                second = s.parse().ok()?;
+            }
+            if parts.next().is_some() || hour > 23 || minute > 59 || !(0.0..60.0).contains(&second) {
+                return None;
+            }
+        }
+        Some(CalendarDate { year: sign * year, month, day, hour, minute, second })
+    }
+
+    /// Current UTC date from the system clock
+    pub fn now() -> Self {
+        let unix = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or(0.0);
+        CalendarDate::from_julian_day(UNIX_EPOCH_JD + unix / 86400.0)
+    }
+}
+
+impl std::fmt::Display for CalendarDate {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second.floor() as u32,
+        )
+    }
+}
+
+/// Maps simulation seconds to Julian days: `epoch` at time 0, advancing `days_per_second`
+#[derive(Clone, Copy, Debug)]
+pub struct EphemerisClock {
+    pub epoch: f64,
+    pub days_per_second: f64,
+}
+
+impl EphemerisClock {
+    pub fn new(date: CalendarDate, days_per_second: f64) -> Self {
+        EphemerisClock { epoch: date.julian_day(), days_per_second }
+    }
+
+    pub fn julian_day(&self, time: f32) -> f64 {
+        self.epoch + time as f64 * self.days_per_second
+    }
+
+    pub fn date(&self, time: f32) -> CalendarDate {
+        CalendarDate::from_julian_day(self.julian_day(time))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> CalendarDate {
+        CalendarDate { year, month, day, hour, minute, second: 0.0 }
+    }
+
+    #[test]
+    fn julian_day_of_known_dates() {
+        assert_eq!(julian_day(2000, 1, 1, 12, 0, 0.0), J2000);
+        assert_eq!(julian_day(1970, 1, 1, 0, 0, 0.0), UNIX_EPOCH_JD);
+        // Meeus, example 7.a: 1957 October 4.81
+        assert!((julian_day(1957, 10, 4, 19, 26, 24.0) - 2436116.31).abs() < 1e-6);
+    }
+
+    #[test]
+    fn julian_day_round_trips() {
+        assert_eq!(CalendarDate::from_julian_day(J2000), date(2000, 1, 1, 12, 0));
+        for d in [date(2000, 1, 1, 12, 0), date(2024, 2, 29, 6, 30), date(1900, 3, 1, 0, 0), date(1582, 10, 15, 23, 59), date(2099, 12, 31, 18, 45)] {
+            let back = CalendarDate::from_julian_day(d.julian_day());
+            assert_eq!((back.year, back.month, back.day, back.hour, back.minute), (d.year, d.month, d.day, d.hour, d.minute), "{d}");
+            assert!(back.second.abs() < 1e-3 || (60.0 - back.second) < 1e-3, "{d}: {}", back.second);
+        }
+    }
+
+    #[test]
+    fn parse_checks_the_length_of_the_month() {
+        assert_eq!(CalendarDate::parse("2024-02-29"), Some(date(2024, 2, 29, 0, 0)));
+        assert_eq!(CalendarDate::parse("2000-02-29T08:15"), Some(date(2000, 2, 29, 8, 15)));
+        assert_eq!(CalendarDate::parse("2024-02-30"), None);
+        assert_eq!(CalendarDate::parse("2024-02-31"), None);
+        assert_eq!(CalendarDate::parse("2023-02-29"), None);
+        assert_eq!(CalendarDate::parse("1900-02-29"), None);
+        assert_eq!(CalendarDate::parse("2023-04-31"), None);
+        assert_eq!(CalendarDate::parse("2023-12-31"), Some(date(2023, 12, 31, 0, 0)));
+    }
+
+    #[test]
+    fn earth_at_the_march_equinox() {
+        // 2024-03-20 03:06 UTC: the Sun is at ecliptic longitude 0 (equinox of date), so
+        // the Earth is at 180°, minus the ~0.34° of precession since J2000
+        let p = heliocentric(Body::Earth, julian_day(2024, 3, 20, 3, 6, 0.0));
+        let longitude = (-p.z).atan2(p.x).to_degrees().rem_euclid(360.0);
+        assert!((longitude - 179.66).abs() < 0.05, "longitude {longitude}");
+        assert!(p.y.abs() < 1e-4, "latitude {}", p.y);
+        assert!((p.length() - 0.996).abs() < 0.002, "distance {}", p.length());
+    }
+}
@@ -1,4 +1,34 @@
 use raylib::prelude::*;
+
+/// How a fragment's colour combines with the pixel already in the buffer
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlendMode {
+    Opaque,        // dst = src
+    Alpha,         // dst = src * a + dst * (1 - a)
+    Additive,      // dst = dst + src * a
+    Premultiplied, // dst = src + dst * (1 - a), src already multiplied by a
+}
+
+/// Per-material output state: blend equation and whether depth is written
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RenderState {
+    pub blend: BlendMode,
+    pub depth_write: bool,
+}
+
+impl RenderState {
+    pub const OPAQUE: RenderState = RenderState { blend: BlendMode::Opaque, depth_write: true };
+
+    /// Transparent state: blends and leaves depth untouched so geometry behind stays visible
+    pub const fn transparent(blend: BlendMode) -> Self {
+        RenderState { blend, depth_write: false }
+    }
+
+    pub fn is_transparent(&self) -> bool {
+        self.blend != BlendMode::Opaque
+    }
+}
+
 pub struct Framebuffer {
     pub width: u32,
     pub height: u32,
@@ -43,8 +73,81 @@ impl Framebuffer {
             
         }
     }
-    pub fn get_color(&mut self, x: u32, y: u32) {
-        self.color_buffer.get_color(x as i32, y as i32);
+    /// Depth-tests a fragment and combines `color` with the stored pixel according to `state`
+    pub fn blend_pixel(&mut self, x: u32, y: u32, depth: f32, color: Color, state: RenderState) {
+        if x >= self.width || y >= self.height || depth >= self.depth_buffer[(y*self.width + x) as usize] {
+            return;
+        }
+        let out = if state.blend == BlendMode::Opaque {
+            Color::new(color.r, color.g, color.b, 255)
+        } else {
+            let dst = self.color_buffer.get_color(x as i32, y as i32);
+            let a = color.a as f32 / 255.0;
+            let mix = |s: u8, d: u8| {
+                let (s, d) = (s as f32, d as f32);
+                let v = match state.blend {
+                    BlendMode::Alpha => s * a + d * (1.0 - a),
+                    BlendMode::Additive => d + s * a,
+                    BlendMode::Premultiplied => s + d * (1.0 - a),
+                    BlendMode::Opaque => s,
+                };
+                v.round().clamp(0.0, 255.0) as u8
+            };
+            Color::new(mix(color.r, dst.r), mix(color.g, dst.g), mix(color.b, dst.b), 255)
+        };
+        if state.depth_write {
+            self.depth_buffer[(y*self.width + x) as usize] = depth;
+        }
+        self.color_buffer.draw_pixel(x as i32, y as i32, out);
+    }
+
+    /// Paints `color` on every pixel outside `mask` that lies within `width` pixels of it,
+    /// ignoring depth. `mask` has one entry per pixel, row by row.
+    pub fn draw_outline(&mut self, mask: &[bool], width: i32, color: Color) {
+        let (w, h) = (self.width as i32, self.height as i32);
+        let inside = |x: i32, y: i32| x >= 0 && y >= 0 && x < w && y < h && mask[(y * w + x) as usize];
+        // Only the mask's bounding box, grown by the outline width, can change
+        let (mut x0, mut y0, mut x1, mut y1) = (w, h, -1, -1);
+        for i in (0..mask.len()).filter(|&i| mask[i]) {
+            let (x, y) = (i as i32 % w, i as i32 / w);
+            (x0, y0, x1, y1) = (x0.min(x), y0.min(y), x1.max(x), y1.max(y));
+        }
+        for y in (y0 - width).max(0)..=(y1 + width).min(h - 1) {
+            for x in (x0 - width).max(0)..=(x1 + width).min(w - 1) {
+                if inside(x, y) {
+                    continue;
+                }
+                let near = (-width..=width).any(|dy| (-width..=width)
+                    .any(|dx| dx * dx + dy * dy <= width * width && inside(x + dx, y + dy)));
+                if near {
+                    self.color_buffer.draw_pixel(x, y, color);
+                }
+            }
+        }
+    }
+
+    /// Overlay rectangle, clipped to the buffer and drawn over everything (no depth).
+    /// A translucent `color` is alpha-blended over what is there.
+    pub fn fill_rect(&mut self, x: i32, y: i32, width: i32, height: i32, color: Color) {
+        let (x0, y0) = (x.max(0), y.max(0));
+        let (x1, y1) = ((x + width).min(self.width as i32), (y + height).min(self.height as i32));
+        let a = color.a as f32 / 255.0;
+        for py in y0..y1 {
+            for px in x0..x1 {
+                let out = if color.a == 255 {
+                    color
+                } else {
+                    let dst = self.color_buffer.get_color(px, py);
+                    let mix = |s: u8, d: u8| (s as f32 * a + d as f32 * (1.0 - a)).round() as u8;
+                    Color::new(mix(color.r, dst.r), mix(color.g, dst.g), mix(color.b, dst.b), 255)
+                };
+                self.color_buffer.draw_pixel(px, py, out);
+            }
+        }
+    }
+
+    pub fn get_color(&mut self, x: u32, y: u32) -> Color {
+        self.color_buffer.get_color(x as i32, y as i32)
     }
 
     pub fn set_background_color(&mut self, color: Color) {
@@ -60,14 +163,24 @@ impl Framebuffer {
         self.color_buffer.export_image(file_path);
     }
 
+    /// Presents the frame; `message` (e.g. a scene reload error) is drawn over it
     pub fn swap_buffers(
         &self,
         window: &mut RaylibHandle,
         raylib_thread: &RaylibThread,
+        message: Option<&str>,
     ) {
         if let Ok(texture) = window.load_texture_from_image(raylib_thread, &self.color_buffer) {
             let mut renderer = window.begin_drawing(raylib_thread);
             renderer.draw_texture(&texture, 0, 0, Color::WHITE);
+            if let Some(message) = message {
+                let lines: Vec<&str> = message.lines().collect();
+                let top = self.height as i32 - 12 - 22 * lines.len() as i32;
+                renderer.draw_rectangle(0, top - 8, self.width as i32, self.height as i32 - top + 8, Color::new(0, 0, 0, 190));
+                for (i, line) in lines.iter().enumerate() {
+                    renderer.draw_text(line, 12, top + 22 * i as i32, 20, Color::new(255, 110, 90, 255));
+                }
+            }
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,338 @@
+#![allow(dead_code)]
+
+use raylib::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Space in which colours are blended between two stops. Stop colours are always
+/// written as the values shown on screen (sRGB, 0 … 1), like every other colour.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum ColorSpace {
+    #[default]
+    Srgb,      // straight blend of the stored values; darkens and greys mid-way
+    LinearRgb, // blend of the light intensities; brighter mid-way
+    Oklab,     // perceptual: even steps of lightness and hue
+}
+
+impl ColorSpace {
+    pub const ALL: [ColorSpace; 3] = [ColorSpace::Srgb, ColorSpace::LinearRgb, ColorSpace::Oklab];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorSpace::Srgb => "sRGB",
+            ColorSpace::LinearRgb => "RGB lineal",
+            ColorSpace::Oklab => "Oklab",
+        }
+    }
+
+    pub fn next(self) -> ColorSpace {
+        ColorSpace::ALL[(ColorSpace::ALL.iter().position(|&s| s == self).unwrap_or(0) + 1) % ColorSpace::ALL.len()]
+    }
+
+    /// Blends two screen colours `k` (0 … 1) of the way from `a` to `b`
+    pub fn mix(self, a: Vector3, b: Vector3, k: f32) -> Vector3 {
+        let lerp = |a: Vector3, b: Vector3| a + (b - a) * k;
+        match self {
+            ColorSpace::Srgb => lerp(a, b),
+            ColorSpace::LinearRgb => encode(lerp(decode(a), decode(b))),
+            ColorSpace::Oklab => encode(oklab_to_linear(lerp(linear_to_oklab(decode(a)), linear_to_oklab(decode(b))))),
+        }
+    }
+}
+
+// --- sRGB transfer curve and Oklab (Björn Ottosson, 2020) ---
+
+fn decode(c: Vector3) -> Vector3 {
+    let f = |c: f32| {
+        let c = c.clamp(0.0, 1.0);
+        if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+    };
+    Vector3::new(f(c.x), f(c.y), f(c.z))
+}
+
+fn encode(c: Vector3) -> Vector3 {
+    let f = |c: f32| {
+        let c = c.clamp(0.0, 1.0);
+        if c <= 0.0031308 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 }
+    };
+    Vector3::new(f(c.x), f(c.y), f(c.z))
+}
+
+fn linear_to_oklab(c: Vector3) -> Vector3 {
+    let l = (0.4122215 * c.x + 0.5363325 * c.y + 0.051446 * c.z).cbrt();
+    let m = (0.2119035 * c.x + 0.6806995 * c.y + 0.107397 * c.z).cbrt();
+    let s = (0.0883025 * c.x + 0.2817188 * c.y + 0.6299787 * c.z).cbrt();
+    Vector3::new(
+        0.2104543 * l + 0.7936178 * m - 0.004072 * s,
+        1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
+        0.025904 * l + 0.7827718 * m - 0.8086758 * s,
+    )
+}
+
+fn oklab_to_linear(c: Vector3) -> Vector3 {
+    let l = (c.x + 0.3963378 * c.y + 0.2158038 * c.z).powi(3);
+    let m = (c.x - 0.1055613 * c.y - 0.0638542 * c.z).powi(3);
+    let s = (c.x - 0.0894842 * c.y - 1.2914855 * c.z).powi(3);
+    Vector3::new(
+        4.0767417 * l - 3.3077116 * m + 0.2309699 * s,
+        -1.268438 * l + 2.6097574 * m - 0.3413194 * s,
+        -0.0041961 * l - 0.7034186 * m + 1.7076147 * s,
+    )
+}
+
+/// A colour at a position along the gradient
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stop {
+    pub position: f32, // 0 … 1
+    pub color: Vector3,
+}
+
+/// Colour ramp through any number of stops, kept sorted by position. Before the first
+/// stop and after the last one the colour holds.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    pub stops: Vec<Stop>,
+    pub space: ColorSpace,
+}
+
+impl Gradient {
+    pub fn new(mut stops: Vec<Stop>, space: ColorSpace) -> Self {
+        stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+        Gradient { stops, space }
+    }
+
+    /// The sun's original palette: red, yellow, then white with a hint of blue
+    pub fn temperature() -> Self {
+        Gradient::new(vec![
+            Stop { position: 0.0, color: Vector3::new(1.0, 0.2, 0.0) },
+            Stop { position: 0.5, color: Vector3::new(1.0, 1.0, 0.0) },
+            Stop { position: 1.0, color: Vector3::new(1.0, 1.0, 0.3) },
+        ], ColorSpace::Srgb)
+    }
+
+    pub fn sample(&self, t: f32) -> Vector3 {
+        let (Some(first), Some(last)) = (self.stops.first(), self.stops.last()) else {
+            return Vector3::zero();
+        };
+        if t <= first.position {
+            return first.color;
+        }
+        if t >= last.position {
+            return last.color;
+        }
+        let i = self.stops.partition_point(|s| s.position <= t);
+        let (a, b) = (self.stops[i - 1], self.stops[i]);
+        let width = b.position - a.position;
+        let k = if width > 0.0 { (t - a.position) / width } else { 1.0 };
+        self.space.mix(a.color, b.color, k)
+    }
+
+    /// Adds a stop at `position` with the colour the gradient has there, so the ramp
+    /// looks the same until it is edited; returns its index
+    pub fn insert_stop(&mut self, position: f32) -> usize {
+        let stop = Stop { position, color: self.sample(position) };
+        let i = self.stops.partition_point(|s| s.position <= position);
+        self.stops.insert(i, stop);
+        i
+    }
+
+    /// Removes a stop, always keeping at least one
+    pub fn remove_stop(&mut self, index: usize) {
+        if self.stops.len() > 1 && index < self.stops.len() {
+            self.stops.remove(index);
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Gradient, String> {
+        let source = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let desc: GradientDesc = ron::from_str(&source).map_err(|e| format!("{}: {e}", path.display()))?;
+        if desc.stops.is_empty() {
+            return Err(format!("{}: un gradiente necesita al menos una parada", path.display()));
+        }
+        if let Some((position, _)) = desc.stops.iter().find(|(p, _)| !(0.0..=1.0).contains(p)) {
+            return Err(format!("{}: parada en {position}, fuera de 0 … 1", path.display()));
+        }
+        Ok(desc.into())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+        }
+        let text = ron::ser::to_string_pretty(&GradientDesc::from(self), ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())?;
+        std::fs::write(path, text + "\n").map_err(|e| format!("{}: {e}", path.display()))
+    }
+}
+
+// --- Names ---
+
+/// Name of a gradient, as materials store it: a small interned id, so materials stay
+/// `Copy` and shaders look gradients up without comparing strings
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GradientName(u32);
+
+/// The built-in "temperatura", for materials that do not name a gradient
+impl Default for GradientName {
+    fn default() -> Self {
+        GradientName::TEMPERATURE
+    }
+}
+
+/// Every name seen so far; the index is the id. Id 0 is always "temperatura".
+static NAMES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+fn with_names<R>(f: impl FnOnce(&mut Vec<String>) -> R) -> R {
+    let mut names = NAMES.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if names.is_empty() {
+        names.push("temperatura".to_string());
+    }
+    f(&mut names)
+}
+
+impl GradientName {
+    /// The built-in palette the sun and corona use by default
+    pub const TEMPERATURE: GradientName = GradientName(0);
+
+    pub fn new(name: &str) -> Self {
+        with_names(|names| match names.iter().position(|n| n == name) {
+            Some(i) => GradientName(i as u32),
+            None => {
+                names.push(name.to_string());
+                GradientName(names.len() as u32 - 1)
+            }
+        })
+    }
+
+    pub fn as_string(self) -> String {
+        with_names(|names| names[self.0 as usize].clone())
+    }
+}
+
+impl std::fmt::Display for GradientName {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.as_string())
+    }
+}
+
+impl Serialize for GradientName {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.as_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for GradientName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(|name| GradientName::new(&name))
+    }
+}
+
+// --- Library ---
+
+/// Gradients by name: the built-in "temperatura" plus every `*.ron` file of a folder
+/// (`presets/gradientes/` by default), named after the file. A file may replace the
+/// built-in one. Shaders reach it through `Uniforms::gradients`.
+#[derive(Clone, Debug)]
+pub struct Gradients {
+    gradients: BTreeMap<GradientName, Gradient>,
+    pub dir: PathBuf, // where edited gradients are saved
+}
+
+impl Default for Gradients {
+    fn default() -> Self {
+        Gradients {
+            gradients: BTreeMap::from([(GradientName::TEMPERATURE, Gradient::temperature())]),
+            dir: PathBuf::from("gradientes"),
+        }
+    }
+}
+
+impl Gradients {
+    /// Reads the gradients in `dir` (none if it does not exist). Files that fail to load
+    /// are reported in the returned messages and left out.
+    pub fn load(dir: &Path) -> (Gradients, Vec<String>) {
+        let mut library = Gradients { dir: dir.to_path_buf(), ..Gradients::default() };
+        let mut errors = Vec::new();
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return (library, errors);
+        };
+        let mut files: Vec<PathBuf> = entries.filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "ron"))
+            .collect();
+        files.sort();
+        for path in files {
+            let name = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+            match Gradient::load(&path) {
+                Ok(gradient) => {
+                    library.gradients.insert(GradientName::new(&name), gradient);
+                }
+                Err(error) => errors.push(error),
+            }
+        }
+        (library, errors)
+    }
+
+    pub fn get(&self, name: GradientName) -> Option<&Gradient> {
+        self.gradients.get(&name)
+    }
+
+    pub fn get_mut(&mut self, name: GradientName) -> Option<&mut Gradient> {
+        self.gradients.get_mut(&name)
+    }
+
+    pub fn contains(&self, name: GradientName) -> bool {
+        self.gradients.contains_key(&name)
+    }
+
+    /// Colour of gradient `name` at `t`; an unknown name falls back to "temperatura"
+    pub fn sample(&self, name: GradientName, t: f32) -> Vector3 {
+        match self.gradients.get(&name).or_else(|| self.gradients.get(&GradientName::TEMPERATURE)) {
+            Some(gradient) => gradient.sample(t),
+            None => Gradient::temperature().sample(t),
+        }
+    }
+
+    /// All names, alphabetically
+    pub fn names(&self) -> Vec<GradientName> {
+        let mut names: Vec<GradientName> = self.gradients.keys().copied().collect();
+        names.sort_by_key(|n| n.as_string());
+        names
+    }
+
+    /// Writes gradient `name` to `<dir>/<name>.ron`, where the next start picks it up
+    pub fn save(&self, name: GradientName) -> Result<PathBuf, String> {
+        let gradient = self.get(name).ok_or_else(|| format!("gradiente desconocido: '{name}'"))?;
+        let path = self.dir.join(format!("{name}.ron"));
+        gradient.save(&path)?;
+        Ok(path)
+    }
+}
+
+// --- File format (RON): `(space: Oklab, stops: [(0.0, (1.0, 0.2, 0.0)), ...])` ---
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GradientDesc {
+    #[serde(default)]
+    space: ColorSpace,
+    stops: Vec<(f32, (f32, f32, f32))>,
+}
+
+impl From<GradientDesc> for Gradient {
+    fn from(desc: GradientDesc) -> Self {
+        let stops = desc.stops.into_iter()
+            .map(|(position, (r, g, b))| Stop { position, color: Vector3::new(r, g, b) })
+            .collect();
+        Gradient::new(stops, desc.space)
+    }
+}
+
+impl From<&Gradient> for GradientDesc {
+    fn from(gradient: &Gradient) -> Self {
+        GradientDesc {
+            space: gradient.space,
+            stops: gradient.stops.iter().map(|s| (s.position, (s.color.x, s.color.y, s.color.z))).collect(),
+        }
+    }
+}
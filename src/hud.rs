@@ -0,0 +1,66 @@
+#![allow(dead_code)]
+
+use raylib::prelude::*;
+use crate::font::{draw_text, text_height, text_width};
+use crate::framebuffer::Framebuffer;
+
+/// On-screen status panel, drawn into the framebuffer after the scene
+pub struct Hud {
+    pub visible: bool,
+    pub color: Color,
+    pub background: Color,
+    frame_seconds: f32,  // smoothed wall time per frame
+    render_seconds: f32, // smoothed time spent rasterizing the scene
+}
+
+impl Default for Hud {
+    fn default() -> Self {
+        Hud {
+            visible: true,
+            color: Color::new(225, 230, 240, 255),
+            background: Color::new(0, 0, 0, 150),
+            frame_seconds: 0.0,
+            render_seconds: 0.0,
+        }
+    }
+}
+
+impl Hud {
+    /// Feeds one frame's timings (seconds); the readout is averaged so it stays legible
+    pub fn record_timing(&mut self, frame: f32, render: f32) {
+        let blend = if self.frame_seconds == 0.0 { 1.0 } else { 0.1 };
+        self.frame_seconds += (frame - self.frame_seconds) * blend;
+        self.render_seconds += (render - self.render_seconds) * blend;
+    }
+
+    pub fn fps(&self) -> f32 {
+        if self.frame_seconds > 0.0 { 1.0 / self.frame_seconds } else { 0.0 }
+    }
+
+    pub fn timing_line(&self) -> String {
+        format!("{:.0} fps · cuadro {:.1} ms · render {:.1} ms", self.fps(), self.frame_seconds * 1000.0, self.render_seconds * 1000.0)
+    }
+
+    /// Font pixel size for the framebuffer: 1 below 600 px tall, 2 from 600 px, 3 from 900 px
+    pub fn scale(framebuffer: &Framebuffer) -> i32 {
+        (framebuffer.height as i32 / 300).clamp(1, 3)
+    }
+
+    /// Draws `lines` in a translucent panel in the top-left corner
+    pub fn draw(&self, framebuffer: &mut Framebuffer, lines: &[String]) {
+        if !self.visible || lines.is_empty() {
+            return;
+        }
+        let scale = Hud::scale(framebuffer);
+        let text = lines.join("\n");
+        let (margin, padding) = (4 * scale, 4 * scale);
+        framebuffer.fill_rect(
+            margin,
+            margin,
+            text_width(&text, scale) + 2 * padding,
+            text_height(&text, scale) + 2 * padding - scale,
+            self.background,
+        );
+        draw_text(framebuffer, &text, margin + padding, margin + padding, scale, self.color);
+    }
+}
@@ -0,0 +1,576 @@
+#![allow(dead_code)]
+
+use raylib::prelude::*;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use KeyboardKey::*;
+
+/// Everything the viewer can be asked to do from a key, mouse button or gamepad.
+/// The variant names are the ones used in the input file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+pub enum Action {
+    // Cámara
+    OrbitLeft,
+    OrbitRight,
+    OrbitUp,
+    OrbitDown,
+    ZoomIn,
+    ZoomOut,
+    PanLeft,
+    PanRight,
+    PanUp,
+    PanDown,
+    CycleProjection,
+    WidenView,
+    NarrowView,
+    OrbitDrag,       // hold and move the mouse to orbit
+    PanDrag,         // hold and move the mouse to pan
+    PanModifier,     // held while orbit-dragging, pans instead
+    ToggleInertia,
+    CycleCameraMode,
+    FollowNext,      // follow the next entity of the scene
+
+    // Recorrido de cámara
+    RecordKeyframe,  // append the current view to the camera path
+    ClearPath,
+    SavePath,
+    PlayPath,        // start / stop playing the camera path
+
+    // Marcadores de cámara
+    Bookmark1,
+    Bookmark2,
+    Bookmark3,
+    Bookmark4,
+    Bookmark5,
+    Bookmark6,
+    Bookmark7,
+    Bookmark8,
+    Bookmark9,
+    Bookmark0,
+    StoreBookmark,   // held with a bookmark key, saves the view there instead of recalling it
+
+    // Selección
+    Select,          // click (without dragging) on an entity to select it
+    FollowSelected,  // make the selected entity the camera's target
+
+    // Cámara libre
+    FlyForward,
+    FlyBack,
+    FlyLeft,
+    FlyRight,
+    FlyUp,
+    FlyDown,
+    RollLeft,
+    RollRight,
+    LookLeft,
+    LookRight,
+    LookUp,
+    LookDown,
+    LookDrag,        // hold and move the mouse to look around
+    FlyBoost,
+
+    // Sol
+    TempUp,
+    TempDown,
+    IntensityUp,
+    IntensityDown,
+    CycleSunPreset,  // next look from the sun preset library
+
+    // Simulación
+    ToggleNBody,
+    SwitchIntegrator,
+    Pause,
+    StepForward,
+    StepBack,
+    Faster,
+    Slower,
+    Reverse,
+    ResetSpeed,
+    ToggleFixedStep,
+    JumpStart,
+    JumpForward,
+    JumpBack,
+    JumpTo,
+    Reload,
+
+    // Pantalla
+    ToggleHud,
+    TogglePanel,     // material parameter panel
+}
+
+impl Action {
+    pub const ALL: [Action; 72] = [
+        Action::OrbitLeft, Action::OrbitRight, Action::OrbitUp, Action::OrbitDown,
+        Action::ZoomIn, Action::ZoomOut,
+        Action::PanLeft, Action::PanRight, Action::PanUp, Action::PanDown,
+        Action::CycleProjection, Action::WidenView, Action::NarrowView,
+        Action::OrbitDrag, Action::PanDrag, Action::PanModifier, Action::ToggleInertia,
+        Action::CycleCameraMode, Action::FollowNext,
+        Action::RecordKeyframe, Action::ClearPath, Action::SavePath, Action::PlayPath,
+        Action::Bookmark1, Action::Bookmark2, Action::Bookmark3, Action::Bookmark4, Action::Bookmark5,
+        Action::Bookmark6, Action::Bookmark7, Action::Bookmark8, Action::Bookmark9, Action::Bookmark0,
+        Action::StoreBookmark,
+        Action::Select, Action::FollowSelected,
+        Action::FlyForward, Action::FlyBack, Action::FlyLeft, Action::FlyRight, Action::FlyUp, Action::FlyDown,
+        Action::RollLeft, Action::RollRight,
+        Action::LookLeft, Action::LookRight, Action::LookUp, Action::LookDown, Action::LookDrag, Action::FlyBoost,
+        Action::TempUp, Action::TempDown, Action::IntensityUp, Action::IntensityDown, Action::CycleSunPreset,
+        Action::ToggleNBody, Action::SwitchIntegrator,
+        Action::Pause, Action::StepForward, Action::StepBack, Action::Faster, Action::Slower,
+        Action::Reverse, Action::ResetSpeed, Action::ToggleFixedStep,
+        Action::JumpStart, Action::JumpForward, Action::JumpBack, Action::JumpTo,
+        Action::Reload,
+        Action::ToggleHud, Action::TogglePanel,
+    ];
+
+    /// Bookmark actions by slot number (slot 0 is the 0 key, last on the keyboard)
+    pub const BOOKMARKS: [Action; 10] = [
+        Action::Bookmark0, Action::Bookmark1, Action::Bookmark2, Action::Bookmark3, Action::Bookmark4,
+        Action::Bookmark5, Action::Bookmark6, Action::Bookmark7, Action::Bookmark8, Action::Bookmark9,
+    ];
+
+    /// Built-in bindings, used for every action the input file does not mention
+    pub fn default_bindings(self) -> Vec<Binding> {
+        use Binding::*;
+        use GamepadAxis::*;
+        use GamepadButton::*;
+        match self {
+            Action::OrbitLeft => vec![Key(KEY_A), Axis(GAMEPAD_AXIS_LEFT_X, -1.0)],
+            Action::OrbitRight => vec![Key(KEY_D), Axis(GAMEPAD_AXIS_LEFT_X, 1.0)],
+            Action::OrbitUp => vec![Key(KEY_W), Axis(GAMEPAD_AXIS_LEFT_Y, -1.0)],
+            Action::OrbitDown => vec![Key(KEY_S), Axis(GAMEPAD_AXIS_LEFT_Y, 1.0)],
+            Action::ZoomIn => vec![Key(KEY_R), Wheel(1.0), Gamepad(GAMEPAD_BUTTON_RIGHT_TRIGGER_1)],
+            Action::ZoomOut => vec![Key(KEY_F), Wheel(-1.0), Gamepad(GAMEPAD_BUTTON_LEFT_TRIGGER_1)],
+            Action::PanLeft => vec![Key(KEY_Q), Axis(GAMEPAD_AXIS_RIGHT_X, -1.0)],
+            Action::PanRight => vec![Key(KEY_E), Axis(GAMEPAD_AXIS_RIGHT_X, 1.0)],
+            Action::PanUp => vec![Key(KEY_C), Axis(GAMEPAD_AXIS_RIGHT_Y, -1.0)],
+            Action::PanDown => vec![Key(KEY_Z), Axis(GAMEPAD_AXIS_RIGHT_Y, 1.0)],
+            Action::CycleProjection => vec![Key(KEY_P)],
+            Action::WidenView => vec![Key(KEY_LEFT_BRACKET)],
+            Action::NarrowView => vec![Key(KEY_RIGHT_BRACKET)],
+            Action::OrbitDrag => vec![Mouse(MouseButton::MOUSE_BUTTON_LEFT)],
+            Action::PanDrag => vec![Mouse(MouseButton::MOUSE_BUTTON_MIDDLE)],
+            Action::PanModifier => vec![Key(KEY_LEFT_SHIFT), Key(KEY_RIGHT_SHIFT)],
+            Action::ToggleInertia => vec![Key(KEY_I)],
+            Action::CycleCameraMode => vec![Key(KEY_M), Gamepad(GAMEPAD_BUTTON_MIDDLE_LEFT)],
+            Action::FollowNext => vec![Key(KEY_TAB), Gamepad(GAMEPAD_BUTTON_RIGHT_FACE_RIGHT)],
+            Action::RecordKeyframe => vec![Key(KEY_K)],
+            Action::ClearPath => vec![Key(KEY_DELETE)],
+            Action::SavePath => vec![Key(KEY_F6)],
+            Action::PlayPath => vec![Key(KEY_F7), Gamepad(GAMEPAD_BUTTON_RIGHT_FACE_UP)],
+            Action::Bookmark1 => vec![Key(KEY_ONE)],
+            Action::Bookmark2 => vec![Key(KEY_TWO)],
+            Action::Bookmark3 => vec![Key(KEY_THREE)],
+            Action::Bookmark4 => vec![Key(KEY_FOUR)],
+            Action::Bookmark5 => vec![Key(KEY_FIVE)],
+            Action::Bookmark6 => vec![Key(KEY_SIX)],
+            Action::Bookmark7 => vec![Key(KEY_SEVEN)],
+            Action::Bookmark8 => vec![Key(KEY_EIGHT)],
+            Action::Bookmark9 => vec![Key(KEY_NINE)],
+            Action::Bookmark0 => vec![Key(KEY_ZERO)],
+            Action::StoreBookmark => vec![Key(KEY_LEFT_CONTROL), Key(KEY_RIGHT_CONTROL)],
+            Action::Select => vec![Mouse(MouseButton::MOUSE_BUTTON_LEFT)],
+            Action::FollowSelected => vec![Key(KEY_G)],
+            Action::FlyForward => vec![Key(KEY_W), Axis(GAMEPAD_AXIS_LEFT_Y, -1.0)],
+            Action::FlyBack => vec![Key(KEY_S), Axis(GAMEPAD_AXIS_LEFT_Y, 1.0)],
+            Action::FlyLeft => vec![Key(KEY_A), Axis(GAMEPAD_AXIS_LEFT_X, -1.0)],
+            Action::FlyRight => vec![Key(KEY_D), Axis(GAMEPAD_AXIS_LEFT_X, 1.0)],
+            Action::FlyUp => vec![Key(KEY_E), Gamepad(GAMEPAD_BUTTON_RIGHT_TRIGGER_1)],
+            Action::FlyDown => vec![Key(KEY_Q), Gamepad(GAMEPAD_BUTTON_LEFT_TRIGGER_1)],
+            Action::RollLeft => vec![Key(KEY_Z), Gamepad(GAMEPAD_BUTTON_LEFT_TRIGGER_2)],
+            Action::RollRight => vec![Key(KEY_C), Gamepad(GAMEPAD_BUTTON_RIGHT_TRIGGER_2)],
+            Action::LookLeft => vec![Axis(GAMEPAD_AXIS_RIGHT_X, -1.0)],
+            Action::LookRight => vec![Axis(GAMEPAD_AXIS_RIGHT_X, 1.0)],
+            Action::LookUp => vec![Axis(GAMEPAD_AXIS_RIGHT_Y, -1.0)],
+            Action::LookDown => vec![Axis(GAMEPAD_AXIS_RIGHT_Y, 1.0)],
+            Action::LookDrag => vec![Mouse(MouseButton::MOUSE_BUTTON_LEFT)],
+            Action::FlyBoost => vec![Key(KEY_LEFT_SHIFT), Key(KEY_RIGHT_SHIFT)],
+            Action::TempUp => vec![Key(KEY_RIGHT), Gamepad(GAMEPAD_BUTTON_LEFT_FACE_RIGHT)],
+            Action::TempDown => vec![Key(KEY_LEFT), Gamepad(GAMEPAD_BUTTON_LEFT_FACE_LEFT)],
+            Action::IntensityUp => vec![Key(KEY_UP), Gamepad(GAMEPAD_BUTTON_LEFT_FACE_UP)],
+            Action::IntensityDown => vec![Key(KEY_DOWN), Gamepad(GAMEPAD_BUTTON_LEFT_FACE_DOWN)],
+            Action::CycleSunPreset => vec![Key(KEY_B)],
+            Action::ToggleNBody => vec![Key(KEY_N)],
+            Action::SwitchIntegrator => vec![Key(KEY_V)],
+            Action::Pause => vec![Key(KEY_SPACE), Gamepad(GAMEPAD_BUTTON_MIDDLE_RIGHT)],
+            Action::StepForward => vec![Key(KEY_PERIOD)],
+            Action::StepBack => vec![Key(KEY_COMMA)],
+            Action::Faster => vec![Key(KEY_EQUAL), Key(KEY_KP_ADD)],
+            Action::Slower => vec![Key(KEY_MINUS), Key(KEY_KP_SUBTRACT)],
+            Action::Reverse => vec![Key(KEY_X)],
+            Action::ResetSpeed => vec![Key(KEY_BACKSPACE)],
+            Action::ToggleFixedStep => vec![Key(KEY_T)],
+            Action::JumpStart => vec![Key(KEY_HOME)],
+            Action::JumpForward => vec![Key(KEY_PAGE_UP)],
+            Action::JumpBack => vec![Key(KEY_PAGE_DOWN)],
+            Action::JumpTo => vec![Key(KEY_J)],
+            Action::Reload => vec![Key(KEY_F5)],
+            Action::ToggleHud => vec![Key(KEY_F1)],
+            Action::TogglePanel => vec![Key(KEY_F2)],
+        }
+    }
+}
+
+/// Where an action is read. Orbit and fly actions are never active at the same time,
+/// so they may share inputs (WASD moves either camera); global ones may not share any.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Context {
+    Global,
+    Orbit, // orbit and follow camera modes
+    Fly,   // free-fly camera mode
+}
+
+impl Action {
+    pub fn context(self) -> Context {
+        match self {
+            Action::OrbitLeft | Action::OrbitRight | Action::OrbitUp | Action::OrbitDown
+            | Action::ZoomIn | Action::ZoomOut
+            | Action::PanLeft | Action::PanRight | Action::PanUp | Action::PanDown
+            | Action::OrbitDrag | Action::PanDrag | Action::PanModifier => Context::Orbit,
+            Action::FlyForward | Action::FlyBack | Action::FlyLeft | Action::FlyRight
+            | Action::FlyUp | Action::FlyDown | Action::RollLeft | Action::RollRight
+            | Action::LookLeft | Action::LookRight | Action::LookUp | Action::LookDown
+            | Action::LookDrag | Action::FlyBoost => Context::Fly,
+            _ => Context::Global,
+        }
+    }
+
+    /// Press and hold to drag with the mouse
+    fn is_drag(self) -> bool {
+        matches!(self, Action::OrbitDrag | Action::PanDrag | Action::LookDrag)
+    }
+
+    /// Whether two actions can be live at once, and so must not share an input. A click
+    /// and a drag on the same button are told apart by whether the mouse moved.
+    fn overlaps(self, other: Action) -> bool {
+        if (self == Action::Select && other.is_drag()) || (other == Action::Select && self.is_drag()) {
+            return false;
+        }
+        let (a, b) = (self.context(), other.context());
+        a == Context::Global || b == Context::Global || a == b
+    }
+}
+
+/// One physical input. Axes carry the direction that triggers the action (+1 or -1).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Binding {
+    Key(KeyboardKey),
+    Mouse(MouseButton),
+    Wheel(f32),
+    Gamepad(GamepadButton),
+    Axis(GamepadAxis, f32),
+}
+
+const GAMEPAD: i32 = 0;   // only the first gamepad is read
+const DEAD_ZONE: f32 = 0.2;
+
+impl Binding {
+    /// 0 … 1, analog for sticks past the dead zone; for the wheel, the notches
+    /// turned this frame in the binding's direction
+    fn value(&self, window: &RaylibHandle) -> f32 {
+        match *self {
+            Binding::Key(key) => window.is_key_down(key) as u8 as f32,
+            Binding::Mouse(button) => window.is_mouse_button_down(button) as u8 as f32,
+            Binding::Wheel(sign) => (window.get_mouse_wheel_move() * sign).max(0.0),
+            Binding::Gamepad(button) => {
+                (window.is_gamepad_available(GAMEPAD) && window.is_gamepad_button_down(GAMEPAD, button)) as u8 as f32
+            }
+            Binding::Axis(axis, sign) => {
+                if !window.is_gamepad_available(GAMEPAD) {
+                    return 0.0;
+                }
+                let v = window.get_gamepad_axis_movement(GAMEPAD, axis) * sign;
+                if v > DEAD_ZONE { ((v - DEAD_ZONE) / (1.0 - DEAD_ZONE)).min(1.0) } else { 0.0 }
+            }
+        }
+    }
+
+    /// Two bindings conflict when the same physical input would fire both
+    fn same_input(&self, other: &Binding) -> bool {
+        match (*self, *other) {
+            (Binding::Key(a), Binding::Key(b)) => a == b,
+            (Binding::Mouse(a), Binding::Mouse(b)) => a == b,
+            (Binding::Wheel(a), Binding::Wheel(b)) => a.signum() == b.signum(),
+            (Binding::Gamepad(a), Binding::Gamepad(b)) => a == b,
+            (Binding::Axis(a, s), Binding::Axis(b, t)) => a == b && s.signum() == t.signum(),
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            Binding::Key(key) => write!(f, "Key(\"{}\")", lookup_name(KEYS, key).unwrap_or("?")),
+            Binding::Mouse(button) => write!(f, "Mouse(\"{}\")", lookup_name(MOUSE_BUTTONS, button).unwrap_or("?")),
+            Binding::Wheel(sign) => write!(f, "Wheel({})", sign.signum()),
+            Binding::Gamepad(button) => write!(f, "Gamepad(\"{}\")", lookup_name(GAMEPAD_BUTTONS, button).unwrap_or("?")),
+            Binding::Axis(axis, sign) => write!(f, "Axis(\"{}\", {})", lookup_name(GAMEPAD_AXES, axis).unwrap_or("?"), sign.signum()),
+        }
+    }
+}
+
+/// Current state of every action, refreshed once per frame by `update`
+pub struct InputMap {
+    bindings: BTreeMap<Action, Vec<Binding>>,
+    values: BTreeMap<Action, f32>,
+    previous: BTreeMap<Action, f32>,
+    impulses: BTreeMap<Action, f32>, // wheel notches this frame, kept apart from held inputs
+    mouse_delta: Vector2,
+    mouse_blocked: bool, // the pointer belongs to the on-screen panel
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        InputMap::new(Action::ALL.iter().map(|&a| (a, a.default_bindings())).collect())
+    }
+}
+
+impl InputMap {
+    fn new(bindings: BTreeMap<Action, Vec<Binding>>) -> Self {
+        InputMap {
+            bindings,
+            values: BTreeMap::new(),
+            previous: BTreeMap::new(),
+            impulses: BTreeMap::new(),
+            mouse_delta: Vector2::zero(),
+            mouse_blocked: false,
+        }
+    }
+
+    /// Reads the input file: a map from action to its list of bindings, e.g.
+    /// `{ ZoomIn: [Key("R"), Wheel(1)] }`. Actions left out keep their defaults.
+    /// Fails on unknown names and on inputs bound to two actions of the same context.
+    pub fn load(path: &Path) -> Result<InputMap, String> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+        InputMap::parse(&source).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    pub fn parse(source: &str) -> Result<InputMap, String> {
+        let desc: BTreeMap<Action, Vec<BindingDesc>> = ron::from_str(source).map_err(|e| e.to_string())?;
+        let mut map = InputMap::default();
+        for (action, list) in desc {
+            let bindings = list.iter().map(BindingDesc::build).collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("{action:?}: {e}"))?;
+            map.bindings.insert(action, bindings);
+        }
+        let conflicts = map.conflicts();
+        if !conflicts.is_empty() {
+            return Err(conflicts.join("\n"));
+        }
+        Ok(map)
+    }
+
+    /// One message per input bound to two actions that can be active together
+    pub fn conflicts(&self) -> Vec<String> {
+        let all: Vec<(Action, &Binding)> = self.bindings.iter()
+            .flat_map(|(&action, list)| list.iter().map(move |b| (action, b)))
+            .collect();
+        let mut messages = Vec::new();
+        for (i, (a, binding)) in all.iter().enumerate() {
+            for (b, other) in &all[i + 1..] {
+                if a != b && a.overlaps(*b) && binding.same_input(other) {
+                    messages.push(format!("conflicto: {binding} está asignado a {a:?} y a {b:?}"));
+                }
+            }
+        }
+        messages
+    }
+
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        self.bindings.get(&action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// While blocked, mouse buttons, wheel and motion read as idle, so clicks and drags
+    /// over the parameter panel do not reach the camera or the picking
+    pub fn block_mouse(&mut self, blocked: bool) {
+        self.mouse_blocked = blocked;
+    }
+
+    /// Samples every binding; call once per frame before querying actions
+    pub fn update(&mut self, window: &RaylibHandle) {
+        self.previous = std::mem::take(&mut self.values);
+        let blocked = self.mouse_blocked;
+        let live = |b: &&Binding| !blocked || !matches!(b, Binding::Mouse(_) | Binding::Wheel(_));
+        for (&action, list) in &self.bindings {
+            let (wheel, held): (Vec<&Binding>, Vec<&Binding>) = list.iter().filter(live).partition(|b| matches!(b, Binding::Wheel(_)));
+            self.values.insert(action, held.iter().map(|b| b.value(window)).fold(0.0, f32::max));
+            self.impulses.insert(action, wheel.iter().map(|b| b.value(window)).sum());
+        }
+        self.mouse_delta = if blocked { Vector2::zero() } else { window.get_mouse_delta() };
+    }
+
+    /// Strength of the action this frame, 0 … 1
+    pub fn value(&self, action: Action) -> f32 {
+        self.values.get(&action).copied().unwrap_or(0.0)
+    }
+
+    pub fn down(&self, action: Action) -> bool {
+        self.value(action) > 0.5
+    }
+
+    /// True only on the frame the action starts
+    pub fn pressed(&self, action: Action) -> bool {
+        self.down(action) && self.previous.get(&action).copied().unwrap_or(0.0) <= 0.5
+    }
+
+    /// True only on the frame the action ends
+    pub fn released(&self, action: Action) -> bool {
+        !self.down(action) && self.previous.get(&action).copied().unwrap_or(0.0) > 0.5
+    }
+
+    /// `positive` minus `negative`, -1 … 1
+    pub fn axis(&self, negative: Action, positive: Action) -> f32 {
+        self.value(positive) - self.value(negative)
+    }
+
+    /// One-off steps this frame (wheel notches), not affected by how long a frame takes
+    pub fn impulse(&self, action: Action) -> f32 {
+        self.impulses.get(&action).copied().unwrap_or(0.0)
+    }
+
+    pub fn impulse_axis(&self, negative: Action, positive: Action) -> f32 {
+        self.impulse(positive) - self.impulse(negative)
+    }
+
+    /// Mouse movement this frame, pixels
+    pub fn mouse_delta(&self) -> Vector2 {
+        self.mouse_delta
+    }
+}
+
+// --- File format ---
+
+#[derive(Deserialize)]
+enum BindingDesc {
+    Key(String),
+    Mouse(String),
+    Wheel(f32),
+    Gamepad(String),
+    Axis(String, f32),
+}
+
+impl BindingDesc {
+    fn build(&self) -> Result<Binding, String> {
+        let sign = |s: f32| if s < 0.0 { -1.0 } else { 1.0 };
+        Ok(match self {
+            BindingDesc::Key(name) => Binding::Key(lookup(KEYS, name, "tecla desconocida")?),
+            BindingDesc::Mouse(name) => Binding::Mouse(lookup(MOUSE_BUTTONS, name, "botón del ratón desconocido")?),
+            BindingDesc::Wheel(s) => Binding::Wheel(sign(*s)),
+            BindingDesc::Gamepad(name) => Binding::Gamepad(lookup(GAMEPAD_BUTTONS, name, "botón del mando desconocido")?),
+            BindingDesc::Axis(name, s) => Binding::Axis(lookup(GAMEPAD_AXES, name, "eje del mando desconocido")?, sign(*s)),
+        })
+    }
+}
+
+fn lookup<T: Copy>(table: &[(&str, T)], name: &str, what: &str) -> Result<T, String> {
+    table.iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|&(_, v)| v)
+        .ok_or_else(|| format!("{what}: '{name}'"))
+}
+
+fn lookup_name<T: PartialEq>(table: &[(&'static str, T)], value: T) -> Option<&'static str> {
+    table.iter().find(|(_, v)| *v == value).map(|&(n, _)| n)
+}
+
+/// Key names as written in the input file (raylib's names without `KEY_`)
+const KEYS: &[(&str, KeyboardKey)] = &[
+    ("0", KEY_ZERO), ("1", KEY_ONE), ("2", KEY_TWO), ("3", KEY_THREE), ("4", KEY_FOUR),
+    ("5", KEY_FIVE), ("6", KEY_SIX), ("7", KEY_SEVEN), ("8", KEY_EIGHT), ("9", KEY_NINE),
+    ("A", KEY_A), ("B", KEY_B), ("C", KEY_C), ("D", KEY_D), ("E", KEY_E), ("F", KEY_F),
+    ("G", KEY_G), ("H", KEY_H), ("I", KEY_I), ("J", KEY_J), ("K", KEY_K), ("L", KEY_L),
+    ("M", KEY_M), ("N", KEY_N), ("O", KEY_O), ("P", KEY_P), ("Q", KEY_Q), ("R", KEY_R),
+    ("S", KEY_S), ("T", KEY_T), ("U", KEY_U), ("V", KEY_V), ("W", KEY_W), ("X", KEY_X),
+    ("Y", KEY_Y), ("Z", KEY_Z),
+    ("APOSTROPHE", KEY_APOSTROPHE), ("COMMA", KEY_COMMA), ("MINUS", KEY_MINUS),
+    ("PERIOD", KEY_PERIOD), ("SLASH", KEY_SLASH), ("SEMICOLON", KEY_SEMICOLON), ("EQUAL", KEY_EQUAL),
+    ("LEFT_BRACKET", KEY_LEFT_BRACKET), ("BACKSLASH", KEY_BACKSLASH),
+    ("RIGHT_BRACKET", KEY_RIGHT_BRACKET), ("GRAVE", KEY_GRAVE), ("SPACE", KEY_SPACE),
+    ("ESCAPE", KEY_ESCAPE), ("ENTER", KEY_ENTER), ("TAB", KEY_TAB), ("BACKSPACE", KEY_BACKSPACE),
+    ("INSERT", KEY_INSERT), ("DELETE", KEY_DELETE), ("RIGHT", KEY_RIGHT), ("LEFT", KEY_LEFT),
+    ("DOWN", KEY_DOWN), ("UP", KEY_UP), ("PAGE_UP", KEY_PAGE_UP), ("PAGE_DOWN", KEY_PAGE_DOWN),
+    ("HOME", KEY_HOME), ("END", KEY_END), ("CAPS_LOCK", KEY_CAPS_LOCK),
+    ("SCROLL_LOCK", KEY_SCROLL_LOCK), ("NUM_LOCK", KEY_NUM_LOCK),
+    ("PRINT_SCREEN", KEY_PRINT_SCREEN), ("PAUSE", KEY_PAUSE), ("F1", KEY_F1), ("F2", KEY_F2),
+    ("F3", KEY_F3), ("F4", KEY_F4), ("F5", KEY_F5), ("F6", KEY_F6), ("F7", KEY_F7), ("F8", KEY_F8),
+    ("F9", KEY_F9), ("F10", KEY_F10), ("F11", KEY_F11), ("F12", KEY_F12),
+    ("LEFT_SHIFT", KEY_LEFT_SHIFT), ("LEFT_CONTROL", KEY_LEFT_CONTROL), ("LEFT_ALT", KEY_LEFT_ALT),
+    ("LEFT_SUPER", KEY_LEFT_SUPER), ("RIGHT_SHIFT", KEY_RIGHT_SHIFT),
+    ("RIGHT_CONTROL", KEY_RIGHT_CONTROL), ("RIGHT_ALT", KEY_RIGHT_ALT),
+    ("RIGHT_SUPER", KEY_RIGHT_SUPER), ("KB_MENU", KEY_KB_MENU), ("KP_0", KEY_KP_0),
+    ("KP_1", KEY_KP_1), ("KP_2", KEY_KP_2), ("KP_3", KEY_KP_3), ("KP_4", KEY_KP_4),
+    ("KP_5", KEY_KP_5), ("KP_6", KEY_KP_6), ("KP_7", KEY_KP_7), ("KP_8", KEY_KP_8),
+    ("KP_9", KEY_KP_9), ("KP_DECIMAL", KEY_KP_DECIMAL), ("KP_DIVIDE", KEY_KP_DIVIDE),
+    ("KP_MULTIPLY", KEY_KP_MULTIPLY), ("KP_SUBTRACT", KEY_KP_SUBTRACT), ("KP_ADD", KEY_KP_ADD),
+    ("KP_ENTER", KEY_KP_ENTER), ("KP_EQUAL", KEY_KP_EQUAL),
+];
+
+const MOUSE_BUTTONS: &[(&str, MouseButton)] = &[
+    ("LEFT", MouseButton::MOUSE_BUTTON_LEFT),
+    ("RIGHT", MouseButton::MOUSE_BUTTON_RIGHT),
+    ("MIDDLE", MouseButton::MOUSE_BUTTON_MIDDLE),
+    ("SIDE", MouseButton::MOUSE_BUTTON_SIDE),
+    ("EXTRA", MouseButton::MOUSE_BUTTON_EXTRA),
+    ("FORWARD", MouseButton::MOUSE_BUTTON_FORWARD),
+    ("BACK", MouseButton::MOUSE_BUTTON_BACK),
+];
+
+const GAMEPAD_BUTTONS: &[(&str, GamepadButton)] = &[
+    ("LEFT_FACE_UP", GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP),
+    ("LEFT_FACE_RIGHT", GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT),
+    ("LEFT_FACE_DOWN", GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN),
+    ("LEFT_FACE_LEFT", GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT),
+    ("RIGHT_FACE_UP", GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP),
+    ("RIGHT_FACE_RIGHT", GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT),
+    ("RIGHT_FACE_DOWN", GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN),
+    ("RIGHT_FACE_LEFT", GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_LEFT),
+    ("LEFT_TRIGGER_1", GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_1),
+    ("LEFT_TRIGGER_2", GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_2),
+    ("RIGHT_TRIGGER_1", GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_1),
+    ("RIGHT_TRIGGER_2", GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_2),
+    ("MIDDLE_LEFT", GamepadButton::GAMEPAD_BUTTON_MIDDLE_LEFT),
+    ("MIDDLE", GamepadButton::GAMEPAD_BUTTON_MIDDLE),
+    ("MIDDLE_RIGHT", GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT),
+    ("LEFT_THUMB", GamepadButton::GAMEPAD_BUTTON_LEFT_THUMB),
+    ("RIGHT_THUMB", GamepadButton::GAMEPAD_BUTTON_RIGHT_THUMB),
+];
+
+const GAMEPAD_AXES: &[(&str, GamepadAxis)] = &[
+    ("LEFT_X", GamepadAxis::GAMEPAD_AXIS_LEFT_X),
+    ("LEFT_Y", GamepadAxis::GAMEPAD_AXIS_LEFT_Y),
+    ("RIGHT_X", GamepadAxis::GAMEPAD_AXIS_RIGHT_X),
+    ("RIGHT_Y", GamepadAxis::GAMEPAD_AXIS_RIGHT_Y),
+    ("LEFT_TRIGGER", GamepadAxis::GAMEPAD_AXIS_LEFT_TRIGGER),
+    ("RIGHT_TRIGGER", GamepadAxis::GAMEPAD_AXIS_RIGHT_TRIGGER),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_bindings_do_not_conflict() {
+        assert_eq!(InputMap::default().conflicts(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn shipped_input_file_loads() {
+        InputMap::parse(include_str!("../input.ron")).unwrap();
+    }
+
+    #[test]
+    fn same_key_on_two_global_actions_is_rejected() {
+        assert_eq!(Action::TogglePanel.context(), Context::Global);
+        assert_eq!(Action::CycleSunPreset.context(), Context::Global);
+        let error = InputMap::parse(r#"{ TogglePanel: [Key("F2")], CycleSunPreset: [Key("F2")] }"#).err().expect("conflict");
+        assert!(error.contains("TogglePanel") && error.contains("CycleSunPreset"), "{error}");
+    }
+
+    #[test]
+    fn orbit_and_fly_may_share_a_key() {
+        InputMap::parse(r#"{ OrbitLeft: [Key("KP_4")], FlyLeft: [Key("KP_4")] }"#).unwrap();
+    }
+}
@@ -1,11 +1,163 @@
+#![allow(dead_code)]
+
 use raylib::prelude::*;
+use crate::shadow::{ShadowMap, shadow_visibility};
+
+/// Kind of emitter. Spot cone angles are half-angles in radians.
+#[derive(Clone, Copy, Debug)]
+pub enum LightKind {
+    Point,
+    Directional,
+    Spot { inner_angle: f32, outer_angle: f32 },
+}
 
+#[derive(Clone, Copy, Debug)]
 pub struct Light {
-    pub position: Vector3,
+    pub kind: LightKind,
+    pub position: Vector3,    // ignored by directional lights
+    pub direction: Vector3,   // direction the light travels (directional/spot)
+    pub color: Vector3,       // linear RGB 0..1
+    pub intensity: f32,
+    pub attenuation: Vector3, // (constant, linear, quadratic), point/spot only
+    pub casts_shadows: bool,
+}
+
+/// Light arriving at a surface point.
+pub struct LightSample {
+    pub direction: Vector3, // normalized, from the surface towards the light
+    pub radiance: Vector3,  // color * intensity * attenuation * cone
+    pub distance: f32,      // f32::INFINITY for directional lights
 }
 
 impl Light {
+    /// White point light without falloff.
     pub fn new(position: Vector3) -> Self {
-        Light { position }
+        Light::point(position, Vector3::new(1.0, 1.0, 1.0), 1.0)
+    }
+
+    pub fn point(position: Vector3, color: Vector3, intensity: f32) -> Self {
+        Light {
+            kind: LightKind::Point,
+            position,
+            direction: Vector3::new(0.0, -1.0, 0.0),
+            color,
+            intensity,
+            attenuation: Vector3::new(1.0, 0.0, 0.0),
+            casts_shadows: false,
+        }
+    }
+
+    pub fn directional(direction: Vector3, color: Vector3, intensity: f32) -> Self {
+        Light {
+            kind: LightKind::Directional,
+            position: Vector3::zero(),
+            direction: direction.normalized(),
+            color,
+            intensity,
+            attenuation: Vector3::new(1.0, 0.0, 0.0),
+            casts_shadows: false,
+        }
+    }
+
+    pub fn spot(position: Vector3, direction: Vector3, inner_angle: f32, outer_angle: f32, color: Vector3, intensity: f32) -> Self {
+        Light {
+            kind: LightKind::Spot { inner_angle, outer_angle: outer_angle.max(inner_angle) },
+            position,
+            direction: direction.normalized(),
+            color,
+            intensity,
+            attenuation: Vector3::new(1.0, 0.0, 0.0),
+            casts_shadows: false,
+        }
+    }
+
+    pub fn with_attenuation(mut self, constant: f32, linear: f32, quadratic: f32) -> Self {
+        self.attenuation = Vector3::new(constant, linear, quadratic);
+        self
+    }
+
+    pub fn with_shadows(mut self) -> Self {
+        self.casts_shadows = true;
+        self
     }
-}
\ No newline at end of file
+
+    /// Evaluates the light at a world-space point.
+    pub fn sample(&self, point: Vector3) -> LightSample {
+        if let LightKind::Directional = self.kind {
+            return LightSample {
+                direction: -self.direction,
+                radiance: self.color * self.intensity,
+                distance: f32::INFINITY,
+            };
+        }
+
+        let to_light = self.position - point;
+        let distance = to_light.length();
+        let direction = if distance > 0.0 { to_light / distance } else { Vector3::new(0.0, 1.0, 0.0) };
+
+        let a = self.attenuation;
+        let falloff = 1.0 / (a.x + a.y * distance + a.z * distance * distance).max(1e-4);
+
+        let cone = match self.kind {
+            LightKind::Spot { inner_angle, outer_angle } => {
+                // Smooth edge between the inner and outer cone
+                let cos_theta = (-direction).dot(self.direction);
+                let (cos_inner, cos_outer) = (inner_angle.cos(), outer_angle.cos());
+                if cos_inner - cos_outer > 1e-6 {
+                    ((cos_theta - cos_outer) / (cos_inner - cos_outer)).clamp(0.0, 1.0)
+                } else if cos_theta >= cos_outer { 1.0 } else { 0.0 }
+            }
+            _ => 1.0,
+        };
+
+        LightSample {
+            direction,
+            radiance: self.color * (self.intensity * falloff * cone),
+            distance,
+        }
+    }
+}
+
+/// Lambertian diffuse term: max(N·L, 0)
+#[inline]
+pub fn lambert(normal: Vector3, to_light: Vector3) -> f32 {
+    normal.dot(to_light).max(0.0)
+}
+
+/// Blinn-Phong specular term using the half vector between L and V
+#[inline]
+pub fn blinn_phong(normal: Vector3, to_light: Vector3, to_eye: Vector3, shininess: f32) -> f32 {
+    if normal.dot(to_light) <= 0.0 {
+        return 0.0;
+    }
+    let half = (to_light + to_eye).normalized();
+    normal.dot(half).max(0.0).powf(shininess)
+}
+
+/// Accumulates diffuse + specular contributions of every light at a surface point.
+/// `albedo` tints the diffuse term, `specular` scales the (white) highlight.
+/// Lights with a shadow map in `shadows` are attenuated by their visibility.
+pub fn shade_blinn_phong(
+    lights: &[Light],
+    shadows: &[ShadowMap],
+    point: Vector3,
+    normal: Vector3,
+    eye: Vector3,
+    albedo: Vector3,
+    specular: f32,
+    shininess: f32,
+) -> Vector3 {
+    let to_eye = (eye - point).normalized();
+    let mut out = Vector3::zero();
+    for (i, light) in lights.iter().enumerate() {
+        let s = light.sample(point);
+        let diffuse = lambert(normal, s.direction);
+        if diffuse <= 0.0 {
+            continue;
+        }
+        let visibility = shadow_visibility(shadows, i, point, normal);
+        let spec = blinn_phong(normal, s.direction, to_eye, shininess) * specular;
+        out += s.radiance * (albedo * diffuse + Vector3::new(spec, spec, spec)) * visibility;
+    }
+    out
+}
@@ -2,13 +2,21 @@
 #![allow(unused_imports)]
 #![allow(dead_code)]
 #[inline]
-fn rotate_y(v: Vector3, ang: f32) -> Vector3 {
+pub(crate) fn rotate_y(v: Vector3, ang: f32) -> Vector3 {
     let (s, c) = ang.sin_cos();
     Vector3::new(c*v.x + 0.0*v.y + -s*v.z, v.y, s*v.x + 0.0*v.y + c*v.z)
 }
 
+/// Hermite step from 0 at `e0` to 1 at `e1` (either order), as in GLSL
+#[inline]
+pub(crate) fn smoothstep(e0: f32, e1: f32, x: f32) -> f32 {
+    let t = ((x - e0) / (e1 - e0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
 use raylib::prelude::*;
 use std::f32::consts::PI;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
 mod framebuffer;
@@ -21,18 +29,129 @@ mod entity;
 
 mod uniforms;
 mod procedural;
-use camera::Camera;
-use entity::Entity;
-use framebuffer::Framebuffer;
-use light::Light;
+mod scene;
+mod shadow;
+mod planet;
+mod ring;
+mod orbit;
+mod nbody;
+mod ephemeris;
+mod clock;
+mod params;
+mod mesh;
+mod scene_file;
+mod sun;
+mod watch;
+mod transform;
+mod input;
+mod camera_path;
+mod bookmarks;
+mod picking;
+mod font;
+mod hud;
+mod ui;
+mod panel;
+mod sun_presets;
+mod gradient;
+use camera::{Camera, Projection, Tile};
+use camera_path::CameraPath;
+use bookmarks::Bookmarks;
+use hud::Hud;
+use panel::ParamPanel;
+use sun_presets::SunPresets;
+use gradient::{GradientName, Gradients};
+use params::Params;
+use entity::{Entity, WorldTransform};
+use transform::{Quat, Transform};
+use framebuffer::{BlendMode, Framebuffer, RenderState};
+use light::{Light, shade_blinn_phong};
+use scene::Scene;
+use shadow::{ShadowMap, ShadowSettings};
+use planet::{PlanetMaterial, planet_shader};
+use ring::{RingMaterial, Rings, ring_shader};
+use orbit::{kepler_period, Orbit, OrbitalElements};
+use nbody::{Integrator, NBodySim};
+use ephemeris::{CalendarDate, DisplayScale, EphemerisClock};
+use clock::SimClock;
+use scene_file::{built_in_scene, load_scene, BUILT_IN_DIR};
+use sun::{SunMaterial, solar_flare, sun_shader};
+use watch::FileWatcher;
+use input::{Action, InputMap};
 use uniforms::Uniforms;
 use fragment::Fragment;
-use triangle::triangle;
+use triangle::{triangle, Vertex};
 use crate::{matrix::*, procedural::*, uniforms::*};
 
+#[derive(Clone, Copy)]
 enum VertexShader {
     Identity,
-    SolarFlare,
+    SolarFlare(SunMaterial),
+}
+
+#[derive(Clone, Copy)]
+enum FragmentShader {
+    Solar(SunMaterial),
+    // Non-emissive surface lit by the scene lights
+    Lit { albedo: Vector3, specular: f32, shininess: f32 },
+    // Procedural terrain/ocean/clouds/atmosphere
+    Planet(PlanetMaterial),
+    // Semi-transparent planetary ring, blended over what is behind it
+    Ring(RingMaterial),
+    // Rim glow on a shell around a body (sun corona); front faces only, tinted by a gradient
+    Glow { color: Vector3, power: f32, ramp: GradientName, state: RenderState },
+}
+
+impl FragmentShader {
+    fn render_state(&self) -> RenderState {
+        match self {
+            FragmentShader::Ring(material) => material.state,
+            FragmentShader::Glow { state, .. } => *state,
+            _ => RenderState::OPAQUE,
+        }
+    }
+}
+
+/// Every material by name, for the parameter panel: the Solar/Planet/Ring structs list
+/// their own fields, the inline Lit and Glow materials their variant fields
+impl Params for FragmentShader {
+    fn params(&self) -> Vec<(&'static str, params::ParamValue)> {
+        use params::ParamValue::{Color, Float, Gradient};
+        match self {
+            FragmentShader::Solar(material) => material.params(),
+            FragmentShader::Planet(material) => material.params(),
+            FragmentShader::Ring(material) => material.params(),
+            FragmentShader::Lit { albedo, specular, shininess } => vec![
+                ("albedo", Color([albedo.x, albedo.y, albedo.z])),
+                ("specular", Float(*specular)),
+                ("shininess", Float(*shininess)),
+            ],
+            FragmentShader::Glow { color, power, ramp, .. } => vec![
+                ("color", Color([color.x, color.y, color.z])),
+                ("power", Float(*power)),
+                ("ramp", Gradient(*ramp)),
+            ],
+        }
+    }
+
+    fn set_param(&mut self, name: &str, value: params::ParamValue) -> Result<(), String> {
+        let number = |value: params::ParamValue| value.as_f32().ok_or_else(|| params::type_error(name, "un número", &value));
+        let color = |value: params::ParamValue| value.as_color().ok_or_else(|| params::type_error(name, "un color (r, g, b)", &value));
+        match (self, name) {
+            (FragmentShader::Solar(material), _) => material.set_param(name, value),
+            (FragmentShader::Planet(material), _) => material.set_param(name, value),
+            (FragmentShader::Ring(material), _) => material.set_param(name, value),
+            (FragmentShader::Lit { albedo, .. }, "albedo") => { *albedo = color(value)?; Ok(()) }
+            (FragmentShader::Lit { specular, .. }, "specular") => { *specular = number(value)?; Ok(()) }
+            (FragmentShader::Lit { shininess, .. }, "shininess") => { *shininess = number(value)?; Ok(()) }
+            (FragmentShader::Glow { color: c, .. }, "color") => { *c = color(value)?; Ok(()) }
+            (FragmentShader::Glow { power, .. }, "power") => { *power = number(value)?; Ok(()) }
+            (FragmentShader::Glow { ramp, .. }, "ramp") => {
+                *ramp = value.as_gradient().ok_or_else(|| params::type_error(name, "un nombre de gradiente", &value))?;
+                Ok(())
+            }
+            _ => Err(format!("parámetro desconocido: '{name}'")),
+        }
+    }
 }
 
 #[inline]
@@ -91,84 +210,62 @@ fn fbm(mut p: Vector3, octaves: i32, lacunarity: f32, gain: f32) -> f32 {
     sum
 }
 
-fn temperature_to_rgb(t: f32) -> Vector3 {
-    // t in [0,1]: 0 = red/orange, 1 = white/blue
-    // simple 3-point gradient: red -> yellow -> white
-    let t = t.clamp(0.0, 1.0);
-    if t < 0.5 {
-        // red(1,0.2,0) to yellow(1,1,0)
-        let k = t / 0.5;
-        Vector3::new(1.0, lerp(0.2, 1.0, k), 0.0)
-    } else {
-        // yellow(1,1,0) to white(1,1,1) with slight blue tint
-        let k = (t-0.5)/0.5;
-        Vector3::new(1.0, 1.0, lerp(0.0, 0.3, k))
-    }
-}
-
 fn apply_vertex_shader(v: Vector3, shader: &VertexShader, time: f32) -> Vector3 {
     match shader {
         VertexShader::Identity => v,
-        VertexShader::SolarFlare => {
-            // Displace along pseudo-normal (normalized position) with animated FBM
-            let dir = if v.length() > 0.0 { v.normalized() } else { Vector3::new(0.0,0.0,1.0) };
-            let p = Vector3::new(v.x*0.25, v.y*0.25, v.z*0.25 + time*0.2);
-            let n = fbm(p, 4, 2.0, 0.5);
-            let flare = (n*2.0 - 1.0) * 0.35; // amplitude in object units
-            v + dir * flare
-        }
+        VertexShader::SolarFlare(material) => solar_flare(v, material, time),
     }
 }
 
-fn fragment_shader(fragment: &Fragment, u: &Uniforms) -> Vector3 {
-    // Use object-space direction for stable texturing on the sphere surface
-    let mut dir = fragment.obj_position;
-    let len = (dir.x*dir.x + dir.y*dir.y + dir.z*dir.z).sqrt();
-    if len > 0.0 { dir = Vector3::new(dir.x/len, dir.y/len, dir.z/len); }
-
-    // FBM turbulence driven by object-space, time-cycled
-    let tloop = (u.time % 8.0) / 8.0;
-    let p3 = Vector3::new(dir.x*3.0, dir.y*3.0, tloop*8.0);
-    let turb = fbm(p3, 5, 2.0, 0.55);
-
-    // Core intensity based on how close to the disc center it projects (approx with dir.z)
-    // dir.z ~ facing viewer if camera looks down -Z; use abs to be camera-agnostic
-    let facing = dir.z.abs();
-    let base_core = facing.clamp(0.0, 1.0);
-
-    // User controls: temp in [0,1], intensity scaler ~ [0,2]
-    let intensity = ((base_core * 0.7 + turb * 0.6) * u.intensity).clamp(0.0, 1.0);
-
-    // Temperature affects gradient selection
-    let color_base = temperature_to_rgb(((intensity + u.temp*0.8)*0.7).clamp(0.0,1.0));
-
-    // Emission spikes add energetic flicker
-    let spikes = (value_noise3(Vector3::new(dir.x*10.0 + u.time*1.7, dir.y*10.0 - u.time*1.3, u.time*0.5))*2.0-1.0).abs();
-    let emission = (0.6*intensity + 0.8*spikes).clamp(0.0, 1.5);
-
-    Vector3::new(
-        (color_base.x * emission).clamp(0.0, 1.0),
-        (color_base.y * emission).clamp(0.0, 1.0),
-        (color_base.z * emission).clamp(0.0, 1.0),
-    )
+fn fragment_shader(fragment: &Fragment, shader: &FragmentShader, u: &Uniforms) -> Vector4 {
+    let rgb = match shader {
+        FragmentShader::Solar(material) => sun_shader(fragment, material, u),
+        FragmentShader::Lit { albedo, specular, shininess } => {
+            let lit = shade_blinn_phong(
+                u.lights,
+                u.shadows,
+                fragment.world_position,
+                fragment.normal,
+                u.camera_position,
+                *albedo,
+                *specular,
+                *shininess,
+            );
+            *albedo * u.ambient + lit
+        }
+        FragmentShader::Planet(material) => planet_shader(fragment, material, u),
+        FragmentShader::Ring(material) => return ring_shader(fragment, material, u),
+        FragmentShader::Glow { color, power, ramp, .. } => return glow_shader(fragment, *color, *power, *ramp, u),
+    };
+    Vector4::new(rgb.x, rgb.y, rgb.z, 1.0)
 }
 
+fn glow_shader(fragment: &Fragment, color: Vector3, power: f32, ramp: GradientName, u: &Uniforms) -> Vector4 {
+    let normal = u.normal_matrix.multiply_vector(fragment.obj_position.normalized()).normalized();
+    let to_eye = (u.camera_position - fragment.world_position).normalized();
+    let facing = normal.dot(to_eye);
+    if facing <= 0.0 {
+        return Vector4::new(0.0, 0.0, 0.0, 0.0);
+    }
+    // Strongest over the disc, fading to nothing at the shell's silhouette
+    let glow = facing.powf(power) * u.intensity;
+    let tint = color * u.gradients.sample(ramp, 0.3 + u.temp * 0.6);
+    Vector4::new(tint.x, tint.y, tint.z, glow.clamp(0.0, 1.0))
+}
 
+/// Projects a world-space point to screen space; None if it lies behind the eye.
 fn transform(
-    vertex: Vector3,
-    translation: Vector3,
-    scale: f32,
-    rotation: Vector3,
+    world: Vector3,
     view: &Matrix,
     projection: &Matrix,
     viewport: &Matrix,
-) -> Vector3 {
-    let model : Matrix = create_model_matrix(translation, scale, rotation);
-    let vertex4 = Vector4::new(vertex.x, vertex.y, vertex.z, 1.0);
-
-    let world_transform = multiply_matrix_vector4(&model, &vertex4);
+) -> Option<Vector3> {
+    let world_transform = Vector4::new(world.x, world.y, world.z, 1.0);
     let view_transform = multiply_matrix_vector4(view, &world_transform);
     let projection_transform = multiply_matrix_vector4(projection, &view_transform);
+    if projection_transform.w <= 1e-5 {
+        return None;
+    }
 
     // División por w (NDC)
     let ndc = Vector4::new(
@@ -180,169 +277,857 @@ fn transform(
 
     // Viewport una sola vez (x,y), pero mantenemos depth en NDC [-1,1] para el Z-buffer
     let screen = multiply_matrix_vector4(viewport, &ndc);
-    Vector3::new(screen.x, screen.y, ndc.z)
+    Some(Vector3::new(screen.x, screen.y, ndc.z))
+}
+
+/// Runs the vertex stage and returns world-space positions
+fn world_vertices(
+    model: &Matrix,
+    vertex_array: &[Vector3],
+    vshader: &VertexShader,
+    time: f32,
+) -> Vec<(Vector3, Vector3)> {
+    vertex_array.iter().map(|vertex| {
+        let v_obj = apply_vertex_shader(*vertex, vshader, time);
+        let w = multiply_matrix_vector4(model, &Vector4::new(v_obj.x, v_obj.y, v_obj.z, 1.0));
+        (v_obj, Vector3::new(w.x, w.y, w.z))
+    }).collect()
 }
 
+/// Vertex + Primitive Assembly stage: screen-space triangles of a mesh.
+/// Triangles crossing behind the eye are dropped.
+fn assemble_triangles(
+    model: &Matrix,
+    vertex_array: &[Vector3],
+    vshader: &VertexShader,
+    view: &Matrix,
+    projection: &Matrix,
+    viewport: &Matrix,
+    time: f32,
+) -> Vec<[Vertex; 3]> {
+    let vertices: Vec<Option<Vertex>> = world_vertices(model, vertex_array, vshader, time)
+        .into_iter()
+        .map(|(obj, world)| transform(world, view, projection, viewport).map(|screen| Vertex { screen, obj, world }))
+        .collect();
+
+    vertices.chunks_exact(3)
+        .filter_map(|tri| match tri {
+            [Some(a), Some(b), Some(c)] => Some([*a, *b, *c]),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Rasterization + Fragment Processing stage for one triangle
+fn shade_triangle(framebuffer: &mut Framebuffer, tri: &[Vertex; 3], fshader: &FragmentShader, uniforms: &Uniforms) {
+    let state = fshader.render_state();
+    for fragment in triangle(&tri[0], &tri[1], &tri[2], framebuffer.width, framebuffer.height) {
+        let rgba = fragment_shader(&fragment, fshader, uniforms);
+        if state.is_transparent() && rgba.w <= 0.0 {
+            continue;
+        }
+        framebuffer.blend_pixel(
+            fragment.position.x as u32,
+            fragment.position.y as u32,
+            fragment.depth,
+            vec4_to_color(rgba),
+            state,
+        );
+    }
+}
+
+/// Draws a mesh immediately, in submission order
 pub fn render(
     framebuffer: &mut Framebuffer,
-    translation: Vector3,
-    scale: f32,
-    rotation: Vector3,
+    model: &Matrix,
     vertex_array: &[Vector3],
     vshader: &VertexShader,
+    fshader: &FragmentShader,
+    view: &Matrix,
+    projection: &Matrix,
+    viewport: &Matrix,
+    uniforms: &Uniforms,
+) {
+    let uniforms = &Uniforms { model: *model, normal_matrix: create_normal_matrix(model), ..*uniforms };
+    let triangles = assemble_triangles(model, vertex_array, vshader, view, projection, viewport, uniforms.time);
+    for tri in &triangles {
+        shade_triangle(framebuffer, tri, fshader, uniforms);
+    }
+}
+
+/// A mesh submitted for the frame
+pub struct DrawCall<'a> {
+    pub model: Matrix, // world matrix from the scene graph
+    pub vertices: &'a [Vector3],
+    pub vshader: VertexShader,
+    pub fshader: FragmentShader,
+}
+
+/// Draws a frame: opaque meshes first, then every transparent triangle of every
+/// mesh sorted back-to-front by distance to the camera.
+pub fn render_scene(
+    framebuffer: &mut Framebuffer,
+    draws: &[DrawCall],
+    view: &Matrix,
+    projection: &Matrix,
+    viewport: &Matrix,
+    uniforms: &Uniforms,
+) {
+    // --- Opaque pass ---
+    for d in draws.iter().filter(|d| !d.fshader.render_state().is_transparent()) {
+        render(framebuffer, &d.model, d.vertices, &d.vshader, &d.fshader, view, projection, viewport, uniforms);
+    }
+
+    // --- Transparent pass ---
+    let mut models = Vec::new();
+    let mut triangles: Vec<(f32, usize, [Vertex; 3])> = Vec::new();
+    for (i, d) in draws.iter().enumerate().filter(|(_, d)| d.fshader.render_state().is_transparent()) {
+        models.push((i, d.model));
+        for tri in assemble_triangles(&d.model, d.vertices, &d.vshader, view, projection, viewport, uniforms.time) {
+            let centroid = (tri[0].world + tri[1].world + tri[2].world) / 3.0;
+            let distance = (centroid - uniforms.camera_position).length();
+            triangles.push((distance, models.len() - 1, tri));
+        }
+    }
+    triangles.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    for (_, m, tri) in &triangles {
+        let (draw, model) = models[*m];
+        let u = Uniforms { model, normal_matrix: create_normal_matrix(&model), ..*uniforms };
+        shade_triangle(framebuffer, tri, &draws[draw].fshader, &u);
+    }
+}
+
+/// Selection highlight: the silhouette of `draws` rasterized into a mask and an outline
+/// painted around it over the finished frame, so it shows even behind other bodies
+fn render_outline(
+    framebuffer: &mut Framebuffer,
+    draws: &[DrawCall],
     view: &Matrix,
     projection: &Matrix,
     viewport: &Matrix,
     time: f32,
-    resolution: Vector2,
-    temp: f32,
-    intensity: f32,
+    color: Color,
+    width: i32,
 ) {
-    let light = Light::new(Vector3::new(0.0, 10.0, 0.0));
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    let mut obj_vertices_after_vs = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let v_obj = apply_vertex_shader(*vertex, vshader, time);
-        obj_vertices_after_vs.push(v_obj);
-        let transformed = transform(v_obj, translation, scale, rotation, view, projection, viewport);
-        transformed_vertices.push(transformed);
+    let (w, h) = (framebuffer.width, framebuffer.height);
+    let mut mask = vec![false; (w * h) as usize];
+    for d in draws {
+        for tri in assemble_triangles(&d.model, d.vertices, &d.vshader, view, projection, viewport, time) {
+            for fragment in triangle(&tri[0], &tri[1], &tri[2], w, h) {
+                mask[(fragment.position.y as u32 * w + fragment.position.x as u32) as usize] = true;
+            }
+        }
     }
+    framebuffer.draw_outline(&mask, width, color);
+}
+
+/// Shadow pass: rasterizes an entity into every face of a shadow map, storing light-space depth
+fn render_shadow_depth(
+    shadow_map: &mut ShadowMap,
+    model: &Matrix,
+    vertex_array: &[Vector3],
+    vshader: &VertexShader,
+    time: f32,
+) {
+    let world = world_vertices(model, vertex_array, vshader, time);
+    let res = shadow_map.resolution;
+
+    for face in 0..shadow_map.faces.len() {
+        let (view, projection) = (shadow_map.faces[face].view, shadow_map.faces[face].projection);
+        let vertices: Vec<Option<Vertex>> = world.iter()
+            .map(|&(obj, world)| transform(world, &view, &projection, &shadow_map.viewport).map(|screen| Vertex { screen, obj, world }))
+            .collect();
 
-    // Primitive Assembly Stage
-    let mut triangles = Vec::new();
-    let mut obj_tris = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
-            obj_tris.push([
-                obj_vertices_after_vs[i].clone(),
-                obj_vertices_after_vs[i + 1].clone(),
-                obj_vertices_after_vs[i + 2].clone(),
-            ]);
+        for tri in vertices.chunks_exact(3) {
+            if let [Some(a), Some(b), Some(c)] = tri {
+                for fragment in triangle(a, b, c, res, res) {
+                    let depth = shadow_map.light_depth(fragment.world_position);
+                    shadow_map.write(face, fragment.position.x as u32, fragment.position.y as u32, depth);
+                }
+            }
         }
     }
+}
 
-    // Rasterization Stage
-    let mut fragments = Vec::new();
-    for (tri, obj_tri) in triangles.iter().zip(obj_tris.iter()) {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2], &obj_tri[0], &obj_tri[1], &obj_tri[2], &light));
+/// Builds a shadow map for every shadow-casting light from the current entity positions
+fn build_shadow_maps(scene: &Scene, lights: &[Light], settings: &ShadowSettings, time: f32) -> Vec<ShadowMap> {
+    if !settings.enabled || !scene.entities.iter().any(|e| e.casts_shadow) {
+        return Vec::new();
     }
-    
-    let uniforms = Uniforms {
-        time,
-        resolution,
-        temp,
-        intensity,
-    };
+    let (center, radius) = scene.caster_bounds();
+    let mut maps = Vec::new();
+    for (i, light) in lights.iter().enumerate().filter(|(_, l)| l.casts_shadows) {
+        let mut map = ShadowMap::new(i, light, settings, center, radius);
+        for e in scene.entities.iter().filter(|e| e.casts_shadow) {
+            render_shadow_depth(&mut map, &e.world.matrix, &e.vertices, &e.vshader, time);
+            if let Some(rings) = &e.rings {
+                render_shadow_depth(&mut map, &ring_model(e, rings), &rings.vertices, &VertexShader::Identity, time);
+            }
+        }
+        maps.push(map);
+    }
+    maps
+}
 
-    // Fragment Processing Stage
-    for fragment in fragments {
-        let final_rgb = fragment_shader(&fragment, &uniforms);
-        let out = vec3_to_color(final_rgb);
-        framebuffer.set_current_color(out);
-        framebuffer.set_pixel(
-            fragment.position.x as u32,
-            fragment.position.y as u32,
-            fragment.depth
-        );
+/// Rings sit at the planet's world position and scale, tilted by their own angles
+/// rather than spinning with the planet
+fn ring_model(planet: &Entity, rings: &Rings) -> Matrix {
+    Transform::new(planet.world_position(), Quat::from_euler(rings.tilt), planet.world_scale()).matrix()
+}
+
+/// Seconds between keyframes recorded with K; edit the saved file to retime them
+const KEYFRAME_SPACING: f32 = 3.0;
+
+/// Seconds a recalled bookmark takes to glide into place
+const BOOKMARK_GLIDE: f32 = 1.0;
+
+/// Pixels the mouse may move between press and release and still count as a click
+const CLICK_SLOP: f32 = 4.0;
+
+/// Outline around the selected entity
+const SELECTION_COLOR: Color = Color::new(90, 200, 255, 255);
+
+/// Command-line options
+struct Options {
+    scene: Option<PathBuf>,   // scene file; the built-in demo when absent
+    solar_system: bool,       // built-in solar system instead of the demo
+    date: Option<CalendarDate>,
+    days_per_second: f64,
+    start_time: f32,          // initial simulation time, seconds
+    fixed_step: Option<f32>,  // deterministic playback: seconds per frame
+    tile: Option<Tile>,       // this window is one tile of a larger off-axis view
+    input: Option<PathBuf>,   // key/mouse/gamepad bindings; `input.ron` when present
+    path: PathBuf,            // camera path loaded at start (if it exists) and saved with F6
+    render: Option<PathBuf>,  // offline mode: play the camera path and write every frame here
+    fps: f32,                 // frames per second of the offline render
+    frames: Option<u32>,      // offline frame count; the length of the path by default
+    bookmarks: Option<PathBuf>, // camera bookmarks; the per-user file by default
+    hud: bool,                // status panel shown at start (F1 toggles it)
+    presets: PathBuf,         // where the parameter panel saves material presets; sun presets in its `sol` folder
+    sun_preset: Option<String>, // sun preset applied at start, by name or file
+}
+
+const USAGE: &str = "uso: sol [ESCENA.ron] [--solar-system] [--date AAAA-MM-DD[THH:MM[:SS]]] [--days-per-second N] [--time S] [--fixed-step S] [--tile COL,FILA,COLUMNAS,FILAS] [--input CONTROLES.ron] [--path RECORRIDO.ron] [--render DIR] [--fps N] [--frames N] [--bookmarks MARCADORES.ron] [--no-hud] [--presets DIR] [--sun-preset NOMBRE|ARCHIVO.ron]";
+
+fn parse_options() -> Result<Options, String> {
+    let mut scene = None;
+    let mut solar_system = false;
+    let mut date = None;
+    let mut days_per_second: f64 = 1.0;
+    let mut start_time: f32 = 0.0;
+    let mut fixed_step = None;
+    let mut tile = None;
+    let mut input = None;
+    let mut path = PathBuf::from("camino.ron");
+    let mut render = None;
+    let mut fps: f32 = 30.0;
+    let mut frames = None;
+    let mut bookmarks = None;
+    let mut hud = true;
+    let mut presets = PathBuf::from("presets");
+    let mut sun_preset = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--solar-system" => solar_system = true,
+            "--date" => {
+                let value = args.next().ok_or("--date necesita un valor")?;
+                date = Some(CalendarDate::parse(&value).ok_or(format!("fecha inválida: '{value}'"))?);
+            }
+            "--days-per-second" => {
+                let value = args.next().ok_or("--days-per-second necesita un valor")?;
+                days_per_second = value.parse().map_err(|_| format!("número inválido: '{value}'"))?;
+                if !days_per_second.is_finite() {
+                    return Err(format!("--days-per-second debe ser un número finito: '{value}'"));
+                }
+            }
+            "--time" => {
+                let value = args.next().ok_or("--time necesita un valor")?;
+                start_time = value.parse().map_err(|_| format!("número inválido: '{value}'"))?;
+                if !start_time.is_finite() {
+                    return Err(format!("--time debe ser un número finito: '{value}'"));
+                }
+            }
+            "--fixed-step" => {
+                let value = args.next().ok_or("--fixed-step necesita un valor")?;
+                let step: f32 = value.parse().map_err(|_| format!("número inválido: '{value}'"))?;
+                if !step.is_finite() || step <= 0.0 {
+                    return Err(format!("--fixed-step debe ser positivo: '{value}'"));
+                }
+                fixed_step = Some(step);
+            }
+            "--tile" => {
+                let value = args.next().ok_or("--tile necesita un valor")?;
+                let parts: Vec<u32> = value.split(',').map(|p| p.trim().parse()).collect::<Result<_, _>>()
+                    .map_err(|_| format!("--tile espera COL,FILA,COLUMNAS,FILAS: '{value}'"))?;
+                match parts[..] {
+                    [column, row, columns, rows] if column < columns && row < rows => {
+                        tile = Some(Tile { columns, rows, column, row });
+                    }
+                    _ => return Err(format!("--tile espera COL,FILA,COLUMNAS,FILAS con COL < COLUMNAS y FILA < FILAS: '{value}'")),
+                }
+            }
+            "--input" => input = Some(PathBuf::from(args.next().ok_or("--input necesita un valor")?)),
+            "--path" => path = PathBuf::from(args.next().ok_or("--path necesita un valor")?),
+            "--render" => render = Some(PathBuf::from(args.next().ok_or("--render necesita un valor")?)),
+            "--fps" => {
+                let value = args.next().ok_or("--fps necesita un valor")?;
+                fps = value.parse().map_err(|_| format!("número inválido: '{value}'"))?;
+                if !fps.is_finite() || fps <= 0.0 {
+                    return Err(format!("--fps debe ser positivo: '{value}'"));
+                }
+            }
+            "--frames" => {
+                let value = args.next().ok_or("--frames necesita un valor")?;
+                frames = Some(value.parse().map_err(|_| format!("número inválido: '{value}'"))?);
+            }
+            "--no-hud" => hud = false,
+            "--presets" => presets = PathBuf::from(args.next().ok_or("--presets necesita un valor")?),
+            "--sun-preset" => sun_preset = Some(args.next().ok_or("--sun-preset necesita un valor")?),
+            "--bookmarks" => bookmarks = Some(PathBuf::from(args.next().ok_or("--bookmarks necesita un valor")?)),
+            "--scene" => scene = Some(PathBuf::from(args.next().ok_or("--scene necesita un valor")?)),
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            other if other.starts_with('-') => return Err(format!("opción desconocida: '{other}'\n{USAGE}")),
+            path => scene = Some(PathBuf::from(path)),
+        }
     }
 
+    Ok(Options { scene, solar_system, date, days_per_second, start_time, fixed_step, tile, input, path, render, fps, frames, bookmarks, hud, presets, sun_preset })
+}
+
+/// Warns about gradient names in `params` missing from the library; shaders draw
+/// those with "temperatura" instead
+fn report_unknown_gradients(gradients: &Gradients, owner: &str, params: &[(&str, params::ParamValue)]) {
+    for (name, value) in params {
+        if let Some(ramp) = value.as_gradient().filter(|&ramp| !gradients.contains(ramp)) {
+            eprintln!("{owner}: {name}: gradiente desconocido '{ramp}', se usa 'temperatura'");
+        }
+    }
+}
+
+fn check_gradients(scene: &Scene, gradients: &Gradients) {
+    for e in &scene.entities {
+        report_unknown_gradients(gradients, &e.name, &e.fshader.params());
+        if let Some(rings) = &e.rings {
+            report_unknown_gradients(gradients, &e.name, &rings.material.params());
+        }
+    }
 }
 
 fn main() {
+    let mut options = match parse_options() {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{message}");
+            std::process::exit(2);
+        }
+    };
+
+    // Bindings: the file given with --input, else ./input.ron if there is one, else the defaults
+    let input_path = options.input.clone().or_else(|| Some(PathBuf::from("input.ron")).filter(|p| p.exists()));
+    let mut input = match &input_path {
+        Some(path) => match InputMap::load(path) {
+            Ok(map) => map,
+            Err(error) => {
+                eprintln!("{error}");
+                std::process::exit(2);
+            }
+        },
+        None => InputMap::default(),
+    };
+
+    // Camera path: the --path file when it exists; K records into it, F6 saves it there
+    let mut camera_path = if options.path.exists() {
+        match CameraPath::load(&options.path) {
+            Ok(path) => path,
+            Err(error) => {
+                eprintln!("{error}");
+                std::process::exit(1);
+            }
+        }
+    } else {
+        CameraPath::default()
+    };
+    if options.render.is_some() && camera_path.is_empty() && options.frames.is_none() {
+        eprintln!("--render necesita un recorrido de cámara ({} no existe o está vacío) o --frames", options.path.display());
+        std::process::exit(2);
+    }
+
+    // Camera bookmarks: a broken file is reported and then overwritten by the next Ctrl+digit
+    let bookmarks_path = options.bookmarks.clone().unwrap_or_else(Bookmarks::user_file);
+    let mut bookmarks = Bookmarks::load(&bookmarks_path).unwrap_or_else(|error| {
+        eprintln!("{error}");
+        Bookmarks { path: bookmarks_path.clone(), ..Bookmarks::default() }
+    });
+
+    // Without a scene file, the demo or the solar system: from the scenes folder when it
+    // is there, so it can be edited and hot-reloaded like any other, else the copy built
+    // into the program
+    let built_in = if options.solar_system || options.date.is_some() { "solar_system.ron" } else { "demo.ron" };
+    if options.scene.is_none() {
+        options.scene = Some(Path::new(BUILT_IN_DIR).join(built_in)).filter(|path| path.exists());
+    }
+    let mut scene = match &options.scene {
+        Some(path) => match load_scene(path) {
+            Ok(scene) => scene,
+            Err(error) => {
+                eprintln!("{error}");
+                std::process::exit(1);
+            }
+        },
+        None => match built_in_scene(built_in) {
+            Ok(scene) => scene,
+            Err(error) => {
+                eprintln!("{error}");
+                std::process::exit(1);
+            }
+        },
+    };
+
+    // Colour ramps: the built-in "temperatura" plus the files in <presets>/gradientes
+    let (mut gradients, gradient_errors) = Gradients::load(&options.presets.join("gradientes"));
+    for error in &gradient_errors {
+        eprintln!("{error}");
+    }
+
+    // Sun presets: the files in <presets>/sol, B cycles through them and back to the scene's sun
+    let (mut sun_presets, preset_errors) = SunPresets::load(&options.presets.join("sol"));
+    for error in &preset_errors {
+        eprintln!("{error}");
+    }
+    if let Some(name) = &options.sun_preset {
+        if let Err(error) = sun_presets.select(name) {
+            eprintln!("{error}");
+            std::process::exit(2);
+        }
+    }
+    sun_presets.remember(&mut scene);
+    for preset in &sun_presets.presets {
+        report_unknown_gradients(&gradients, &format!("preset '{}'", preset.0), &preset.1.params());
+    }
+    check_gradients(&scene, &gradients);
+
+    // Ephemeris bodies are placed by calendar date
+    let mut ephemeris_clock = scene.entities.iter().any(|e| e.ephemeris.is_some())
+        .then(|| EphemerisClock::new(options.date.unwrap_or_else(CalendarDate::now), options.days_per_second));
+
     let window_width = 1300;
     let window_height = 600;
 
     let (mut window, raylib_thread) = raylib::init()
         .size(window_width, window_height)
         .title("Wireframe")
+        .resizable()
         .log_level(TraceLogLevel::LOG_WARNING)
         .build();
 
-    let projection = create_projection_matrix(PI/3.0, window_width as f32 / window_height as f32, 0.5, 100.0);
-    let viewport = create_viewport_matrix(0.0, 0.0, window_width as f32, window_height as f32);
+    let mut viewport = create_viewport_matrix(0.0, 0.0, window_width as f32, window_height as f32);
 
     let mut framebuffer = Framebuffer::new(window_width as u32, window_height as u32, Color::BLACK);
-    framebuffer.set_background_color(Color::new(4, 12, 36, 255));
+    framebuffer.set_background_color(scene.background);
 
     let mut temp_control: f32 = 0.5;      // 0 (rojo) … 1 (blanco/azulado)
     let mut intensity_control: f32 = 1.0; // 1 = normal, >1 más brillante
 
-    // --- Scene entities ---
-    let mut entities: Vec<Entity> = vec![
-        // The ship we will follow
-        Entity {
-            name: "sun",
-            translation: Vector3::new(0.0, 0.0, 0.0),
-            rotation: Vector3::new(0.0, 0.0, 0.0),
-            scale: 1.0,
-            vertices: generate_uv_sphere(3.0, 24, 32),
-            vshader: VertexShader::SolarFlare,
-            face_tangent: false,
-        },
-    ];
 
+    let display_scale = DisplayScale::default();
+    if let Some(clock) = &ephemeris_clock {
+        println!("fecha: {} UTC, {} días/s", clock.date(0.0), clock.days_per_second);
+        scene.update_ephemeris(clock.julian_day(options.start_time), &display_scale);
+    }
+    // World positions at the start time, so a parented camera starts next to its parent
+    scene.update_motion(options.start_time);
+    scene.update_transforms();
 
+    let mut camera_parent_position = scene.camera.parent.as_deref()
+        .and_then(|name| scene.find(name))
+        .map(|e| e.world_position());
+    let origin = camera_parent_position.unwrap_or(Vector3::zero());
     let mut camera = Camera::new(
-        Vector3::new(0.0, 0.0, 17.0),
-        Vector3::new(0.0, 0.0, 0.0),
+        scene.camera.eye + origin,
+        scene.camera.target + origin,
         Vector3::new(0.0, 1.0, 0.0),
     );
+    camera.projection = scene.camera.projection;
+    camera.near = scene.camera.near;
+    camera.far = scene.camera.far;
+    camera.set_viewport(framebuffer.width as f32, framebuffer.height as f32);
+    if let Some(tile) = options.tile {
+        camera.tile = tile;
+        camera.projection = Projection::OffAxis { fov_y: camera.fov_y(), tile };
+    }
+    // A scene that follows an entity starts in follow mode, already centred on it
+    if let Some(followed) = scene.camera.follow.as_deref().and_then(|name| scene.find(name)) {
+        camera.set_target(followed.world_position());
+        camera.follow_entity(&followed.name);
+    }
+
+    let shadow_settings = ShadowSettings::default();
+
+    // N-body mode (N to toggle, V to switch integrator)
+    let mut nbody: Option<NBodySim> = None;
+    let mut integrator = Integrator::VelocityVerlet;
+    let nbody_substeps = 8;
+
+    let mut clock = SimClock::new(options.start_time);
+    if let Some(step) = options.fixed_step {
+        clock = clock.with_fixed_step(step);
+    }
+
+    // Hot reload: the scene file and the material/mesh files it uses are polled for
+    // changes (F5 forces a reload); a broken file leaves the current scene running
+    let mut watcher = options.scene.as_ref().map(|_| FileWatcher::new(scene.sources.clone()));
+    let mut reload_error: Option<String> = None;
 
-    let start_time = Instant::now();
+    // Camera path playback time, None when not playing. The offline render plays the
+    // path from the start at a fixed frame rate and writes each frame to disk.
+    let mut path_time: Option<f32> = None;
+
+    // Selection: picked by name so it survives a scene reload; outlined and followable with G
+    let mut selected: Option<String> = None;
+    let mut click_start: Option<Vector2> = None;
+
+    let mut hud = Hud::default();
+    hud.visible = options.hud;
+    // Material parameter panel (F2); "Restablecer" returns to the values loaded here
+    let mut panel = ParamPanel::new(&options.presets);
+    panel.remember(&scene);
+    let mut last_frame = Instant::now();
+    let mut rendered_frames = 0;
+    let render_frames = options.frames.unwrap_or_else(|| (camera_path.duration() * options.fps).ceil() as u32 + 1);
+    if let Some(dir) = &options.render {
+        if let Err(error) = std::fs::create_dir_all(dir) {
+            eprintln!("{}: {error}", dir.display());
+            std::process::exit(1);
+        }
+        if !camera_path.is_empty() {
+            path_time = Some(0.0);
+        }
+    }
 
     while !window.window_should_close() {
+        let frame_time = if options.render.is_some() { 1.0 / options.fps } else { window.get_frame_time() };
+        let wall_time = last_frame.elapsed().as_secs_f32();
+        last_frame = Instant::now();
+        // The render target, viewport and aspect ratio follow the window size
+        if window.is_window_resized() {
+            let (width, height) = (window.get_screen_width().max(1), window.get_screen_height().max(1));
+            framebuffer = Framebuffer::new(width as u32, height as u32, scene.background);
+            viewport = create_viewport_matrix(0.0, 0.0, width as f32, height as f32);
+            camera.set_viewport(width as f32, height as f32);
+        }
         framebuffer.clear();
-        camera.process_input(&window);
+        // Clicks, drags and the wheel over the panel are for the panel only
+        input.block_mouse(panel.capture(&window));
+        input.update(&window);
+        if path_time.is_none() {
+            camera.process_input(&input, frame_time);
+        }
+        clock.process_input(&mut window, &input);
+
+        if let (Some(path), Some(files)) = (&options.scene, &mut watcher) {
+            if files.poll(frame_time) || input.pressed(Action::Reload) {
+                match load_scene(path) {
+                    Ok(mut reloaded) => {
+                        // Keep the view and the simulation time; only the contents change
+                        reloaded.camera = scene.camera.clone();
+                        scene = reloaded;
+                        sun_presets.remember(&mut scene);
+                        panel.remember(&scene);
+                        check_gradients(&scene, &gradients);
+                        framebuffer.set_background_color(scene.background);
+                        if ephemeris_clock.is_none() && scene.entities.iter().any(|e| e.ephemeris.is_some()) {
+                            ephemeris_clock = Some(EphemerisClock::new(options.date.unwrap_or_else(CalendarDate::now), options.days_per_second));
+                        }
+                        // N-body restarts from the reloaded bodies at their current positions
+                        if nbody.is_some() {
+                            if let Some(ephemeris) = &ephemeris_clock {
+                                scene.update_ephemeris(ephemeris.julian_day(clock.time), &display_scale);
+                            }
+                            scene.update_motion(clock.time);
+                            scene.update_transforms();
+                            nbody = Some(NBodySim::from_scene(&scene, clock.time, integrator, nbody_substeps));
+                        }
+                        *files = FileWatcher::new(scene.sources.clone());
+                        reload_error = None;
+                        println!("escena recargada: {}", path.display());
+                    }
+                    Err(error) => {
+                        eprintln!("{error}");
+                        // Also watch the file that failed, in case it is new to the scene
+                        let watched: Vec<PathBuf> = files.files().map(|p| p.to_path_buf())
+                            .chain([PathBuf::from(&error.path)])
+                            .collect();
+                        *files = FileWatcher::new(watched);
+                        reload_error = Some(format!("error al recargar la escena (F5 reintenta):\n{}", error.to_string().lines().take(2).collect::<Vec<_>>().join("\n")));
+                    }
+                }
+            }
+        }
 
-        if window.is_key_down(KeyboardKey::KEY_RIGHT) { temp_control += 0.3 * window.get_frame_time(); }
-        if window.is_key_down(KeyboardKey::KEY_LEFT)  { temp_control -= 0.3 * window.get_frame_time(); }
-        if window.is_key_down(KeyboardKey::KEY_UP)    { intensity_control += 0.5 * window.get_frame_time(); }
-        if window.is_key_down(KeyboardKey::KEY_DOWN)  { intensity_control -= 0.5 * window.get_frame_time(); }
+        if input.pressed(Action::CycleSunPreset) {
+            sun_presets.cycle(&mut scene);
+            panel.remember(&scene);
+            match sun_presets.current_name() {
+                Some(name) => println!("sol: preset '{name}'"),
+                None if sun_presets.presets.is_empty() => println!("sol: no hay presets en {}", options.presets.join("sol").display()),
+                None => println!("sol: material de la escena"),
+            }
+        }
+        temp_control += 0.3 * frame_time * input.axis(Action::TempDown, Action::TempUp);
+        intensity_control += 0.5 * frame_time * input.axis(Action::IntensityDown, Action::IntensityUp);
         temp_control = temp_control.clamp(0.0, 1.0);
         intensity_control = intensity_control.clamp(0.2, 2.0);
 
         // Global time and resolution
-        let time = start_time.elapsed().as_secs_f32();
-        let resolution = Vector2::new(window_width as f32, window_height as f32);
+        let dt = clock.advance(frame_time);
+        let time = clock.time;
+        let resolution = Vector2::new(framebuffer.width as f32, framebuffer.height as f32);
+
+        if input.pressed(Action::ToggleNBody) {
+            match nbody.take() {
+                Some(sim) => {
+                    println!("n-cuerpos desactivado: {}", sim.diagnostics());
+                    sim.release(&mut scene);
+                }
+                None => {
+                    let sim = NBodySim::from_scene(&scene, time, integrator, nbody_substeps);
+                    println!("n-cuerpos activado ({}, {} cuerpos): {}", integrator.name(), sim.bodies.len(), sim.diagnostics());
+                    nbody = Some(sim);
+                }
+            }
+        }
+        if input.pressed(Action::SwitchIntegrator) {
+            integrator = integrator.next();
+            if let Some(sim) = &mut nbody {
+                sim.integrator = integrator;
+            }
+            println!("integrador: {}", integrator.name());
+        }
 
         // --- Update entity motions ---
-        use std::collections::HashMap;
+        if let Some(sim) = &mut nbody {
+            let mergers = sim.step(dt);
+            for m in &mergers {
+                println!("{} absorbió a {}: {}", m.survivor, m.absorbed, sim.diagnostics());
+            }
+            sim.apply(&mut scene, &mergers);
+        }
+        if let Some(clock) = &ephemeris_clock {
+            scene.update_ephemeris(clock.julian_day(time), &display_scale);
+        }
+        scene.update_motion(time);
+        scene.update_transforms();
 
-        
+        // --- Parented camera: carried along by its parent's motion ---
+        if let Some(parent) = scene.camera.parent.as_deref().and_then(|name| scene.find(name)) {
+            let position = parent.world_position();
+            if let Some(previous) = camera_parent_position {
+                camera.translate(position - previous);
+            }
+            camera_parent_position = Some(position);
+        }
 
-        // --- Follow camera: lock target to sun position ---
-        if let Some(sun) = entities.iter().find(|ent| ent.name == "sun") {
-            camera.set_target(sun.translation);
+        // --- Follow camera: Tab moves on to the next entity, the target glides after it ---
+        if input.pressed(Action::FollowNext) && !scene.entities.is_empty() {
+            let current = camera.follow.as_deref().and_then(|name| scene.entities.iter().position(|e| e.name == name));
+            let next = &scene.entities[current.map_or(0, |i| (i + 1) % scene.entities.len())];
+            println!("cámara: siguiendo a '{}'", next.name);
+            camera.follow_entity(&next.name);
+        }
+        if let Some(followed) = camera.follow.as_deref().and_then(|name| scene.find(name)).filter(|_| path_time.is_none()) {
+            let position = followed.world_position();
+            camera.track(position, frame_time);
+        }
+
+        // --- Bookmarks: a digit glides to its saved view, Ctrl+digit saves the current one ---
+        if !clock.is_typing() {
+            for (slot, action) in Action::BOOKMARKS.into_iter().enumerate() {
+                let slot = slot as u8;
+                if !input.pressed(action) {
+                    continue;
+                }
+                if input.down(Action::StoreBookmark) {
+                    bookmarks.store(slot, camera.orbit_view());
+                    match bookmarks.save() {
+                        Ok(()) => println!("marcador {slot} guardado en {}", bookmarks.path.display()),
+                        Err(error) => eprintln!("{error}"),
+                    }
+                } else if let Some(view) = bookmarks.get(slot) {
+                    path_time = None;
+                    camera.glide_to(view, BOOKMARK_GLIDE);
+                    println!("cámara: marcador {slot}");
+                } else {
+                    println!("marcador {slot} vacío (Ctrl+{slot} lo guarda)");
+                }
+            }
+        }
+
+        // --- Camera path: K records the view, F6 saves the path, F7 plays it back ---
+        if input.pressed(Action::RecordKeyframe) && path_time.is_none() {
+            let at = camera_path.record(camera.pose(), KEYFRAME_SPACING);
+            println!("recorrido: fotograma clave {} en t = {at:.1} s", camera_path.keyframes.len());
+        }
+        if input.pressed(Action::ClearPath) {
+            camera_path.keyframes.clear();
+            path_time = None;
+            println!("recorrido: vacío");
+        }
+        if input.pressed(Action::SavePath) {
+            match camera_path.save(&options.path) {
+                Ok(()) => println!("recorrido guardado: {} ({} fotogramas clave)", options.path.display(), camera_path.keyframes.len()),
+                Err(error) => eprintln!("{error}"),
+            }
+        }
+        if input.pressed(Action::PlayPath) {
+            path_time = match path_time {
+                Some(_) => None,
+                None if camera_path.is_empty() => {
+                    println!("recorrido: no hay fotogramas clave (K para grabar)");
+                    None
+                }
+                None => Some(0.0),
+            };
+        }
+        if let Some(at) = path_time {
+            if let Some(pose) = camera_path.sample(at) {
+                camera.apply_pose(&pose);
+            }
+            let finished = !camera_path.looping && at >= camera_path.duration();
+            path_time = if finished && options.render.is_none() { None } else { Some(at + frame_time) };
         }
 
         let view = camera.get_view_matrix();
+        let projection = camera.get_projection_matrix();
+
+        // --- Picking: a click that does not drag selects the entity under the cursor ---
+        if input.pressed(Action::Select) {
+            click_start = Some(window.get_mouse_position());
+        }
+        let clicked = input.released(Action::Select)
+            && click_start.take().is_some_and(|start| (window.get_mouse_position() - start).length() <= CLICK_SLOP);
+        if clicked {
+            let mouse = window.get_mouse_position();
+            let hit = screen_ray(mouse.x + 0.5, mouse.y + 0.5, &view, &projection, &viewport)
+                .and_then(|(origin, direction)| picking::pick(&scene, origin, direction, time));
+            selected = hit.map(|hit| scene.entities[hit.entity].name.clone());
+            match &selected {
+                Some(name) => {
+                    println!("seleccionado: '{name}' (G para seguirlo)");
+                    panel.entity = Some(name.clone());
+                }
+                None => println!("selección vacía"),
+            }
+        }
+        if input.pressed(Action::FollowSelected) {
+            if let Some(entity) = selected.as_deref().and_then(|name| scene.find(name)) {
+                path_time = None;
+                println!("cámara: siguiendo a '{}'", entity.name);
+                camera.follow_entity(&entity.name);
+            }
+        }
 
-        // --- Render all entities ---
-        for e in &entities {
-
-            render(
-                &mut framebuffer,
-                e.translation,
-                e.scale,
-                e.rotation,
-                &e.vertices,
-                &e.vshader,
-                &view,
-                &projection,
-                &viewport,
-                time,
-                resolution,
-                temp_control,
-                intensity_control,
+        let lights = scene.gather_lights();
+        let shadow_maps = build_shadow_maps(&scene, &lights, &shadow_settings, time);
+        let uniforms = Uniforms {
+            time,
+            resolution,
+            temp: temp_control,
+            intensity: intensity_control,
+            lights: &lights,
+            shadows: &shadow_maps,
+            camera_position: camera.eye,
+            ambient: scene.ambient,
+            gradients: &gradients,
+            model: Matrix::identity(),
+            normal_matrix: Matrix3::identity(),
+        };
 
-            );
+        // --- Render all entities (rings and glows go through the sorted transparent pass) ---
+        let mut draws = Vec::new();
+        for e in &scene.entities {
+            draws.push(DrawCall {
+                model: e.world.matrix,
+                vertices: &e.vertices,
+                vshader: e.vshader,
+                fshader: e.fshader,
+            });
+            if let Some(rings) = &e.rings {
+                draws.push(DrawCall {
+                    model: ring_model(e, rings),
+                    vertices: &rings.vertices,
+                    vshader: VertexShader::Identity,
+                    fshader: FragmentShader::Ring(rings.material),
+                });
+            }
+        }
+        let render_start = Instant::now();
+        render_scene(&mut framebuffer, &draws, &view, &projection, &viewport, &uniforms);
+
+        if let Some(entity) = selected.as_deref().and_then(|name| scene.find(name)) {
+            let mut outline = vec![DrawCall { model: entity.world.matrix, vertices: &entity.vertices, vshader: entity.vshader, fshader: entity.fshader }];
+            if let Some(rings) = &entity.rings {
+                outline.push(DrawCall { model: ring_model(entity, rings), vertices: &rings.vertices, vshader: VertexShader::Identity, fshader: FragmentShader::Ring(rings.material) });
+            }
+            render_outline(&mut framebuffer, &outline, &view, &projection, &viewport, time, SELECTION_COLOR, 2);
+        }
+        hud.record_timing(wall_time, render_start.elapsed().as_secs_f32());
+
+        // --- HUD: drawn into the framebuffer, so exported frames show it too ---
+        if input.pressed(Action::ToggleHud) {
+            hud.visible = !hud.visible;
+        }
+        if hud.visible {
+            let mut lines = vec![clock.to_string()];
+            if let Some(ephemeris) = &ephemeris_clock {
+                lines.push(format!("fecha: {} UTC", ephemeris.date(time)));
+            }
+            if let Some(sim) = &nbody {
+                lines.push(format!("n-cuerpos: {}, {} cuerpos", integrator.name(), sim.bodies.len()));
+            }
+            let preset = sun_presets.current_name().map(|name| format!(" · preset {name}")).unwrap_or_default();
+            lines.push(format!("sol: temperatura {:.2} · intensidad {:.2}{preset}", temp_control, intensity_control));
+            let projection_detail = match camera.projection {
+                Projection::Orthographic { height } => format!("{height:.1} u"),
+                _ => format!("{:.0}°", camera.fov_y().to_degrees()),
+            };
+            let mode = match (camera.mode, camera.follow.as_deref()) {
+                (camera::CameraMode::Follow, Some(name)) => format!("siguiendo a '{name}'"),
+                (mode, _) => mode.name().to_string(),
+            };
+            lines.push(format!("cámara: {mode} · {} {projection_detail} · distancia {:.1}", camera.projection.name(), camera.distance));
+            if let Some(at) = path_time {
+                lines.push(format!("recorrido: {:.1} / {:.1} s", at.min(camera_path.duration()), camera_path.duration()));
+            }
+            if let Some(entity) = selected.as_deref().and_then(|name| scene.find(name)) {
+                let p = entity.world_position();
+                lines.push(format!(
+                    "selección: {} · radio {:.2} · a {:.1} · en ({:.1}, {:.1}, {:.1})",
+                    entity.name, entity.bounding_radius(), (p - camera.eye).length(), p.x, p.y, p.z,
+                ));
+            }
+            lines.push(hud.timing_line());
+            hud.draw(&mut framebuffer, &lines);
+        }
+
+        // --- Parameter panel: edits apply to the scene from the next frame on ---
+        if input.pressed(Action::TogglePanel) {
+            panel.visible = !panel.visible;
+        }
+        panel.draw(&mut framebuffer, &mut scene, &mut gradients, &mut sun_presets);
+
+        if let Some(dir) = &options.render {
+            rendered_frames += 1;
+            let file = dir.join(format!("frame_{rendered_frames:05}.png"));
+            framebuffer.render_to_file(&file.to_string_lossy());
+            if rendered_frames >= render_frames {
+                println!("{rendered_frames} fotogramas escritos en {}", dir.display());
+                break;
+            }
         }
 
-        framebuffer.swap_buffers(&mut window, &raylib_thread);
+        framebuffer.swap_buffers(&mut window, &raylib_thread, reload_error.as_deref());
     }
 }
@@ -1,6 +1,7 @@
 #![allow(dead_code)]
 
 use raylib::prelude::*;
+use crate::transform::Quat;
 
 pub fn multiply_matrix_vector4(matrix: &Matrix, vector: &Vector4) -> Vector4 {
     Vector4::new(
@@ -12,6 +13,15 @@ pub fn multiply_matrix_vector4(matrix: &Matrix, vector: &Vector4) -> Vector4 {
     )
 }
 
+/// Transforms a direction (w = 0): rotation and scale apply, translation does not.
+pub fn multiply_matrix_direction(matrix: &Matrix, v: Vector3) -> Vector3 {
+    Vector3::new(
+        matrix.m0 * v.x + matrix.m4 * v.y + matrix.m8 * v.z,
+        matrix.m1 * v.x + matrix.m5 * v.y + matrix.m9 * v.z,
+        matrix.m2 * v.x + matrix.m6 * v.y + matrix.m10 * v.z,
+    )
+}
+
 /// Creates a 4x4 matrix from 16 float values, specified in traditional row-major order.
 pub fn new_matrix4(
     // Row 0
@@ -98,6 +108,28 @@ pub fn create_model_matrix(translation: Vector3, scale: f32, rotation: Vector3)
     let mrst = multiply_matrix_matrix(&mt, &mrs);
     mrst
 }
+/// Rotation matrix of a unit quaternion
+pub fn create_rotation_matrix(q: Quat) -> Matrix {
+    let (x, y, z, w) = (q.x, q.y, q.z, q.w);
+    new_matrix3(
+        1.0 - 2.0*(y*y + z*z), 2.0*(x*y - z*w),       2.0*(x*z + y*w),
+        2.0*(x*y + z*w),       1.0 - 2.0*(x*x + z*z), 2.0*(y*z - x*w),
+        2.0*(x*z - y*w),       2.0*(y*z + x*w),       1.0 - 2.0*(x*x + y*y),
+    )
+}
+
+/// Model matrix from a quaternion rotation and per-axis scale: M = T · R · S
+pub fn create_transform_matrix(translation: Vector3, rotation: Quat, scale: Vector3) -> Matrix {
+    let r = create_rotation_matrix(rotation.normalized());
+    // Scaling the columns of R is R · S; the translation fills the last column
+    Matrix {
+        m0: r.m0 * scale.x, m4: r.m4 * scale.y, m8: r.m8 * scale.z,   m12: translation.x,
+        m1: r.m1 * scale.x, m5: r.m5 * scale.y, m9: r.m9 * scale.z,   m13: translation.y,
+        m2: r.m2 * scale.x, m6: r.m6 * scale.y, m10: r.m10 * scale.z, m14: translation.z,
+        m3: 0.0,            m7: 0.0,            m11: 0.0,             m15: 1.0,
+    }
+}
+
 /// Creates a view matrix using camera position, target, and up vector
 /// This implements a lookAt matrix for camera transformations
 pub fn create_view_matrix(eye: Vector3, target: Vector3, up: Vector3) -> Matrix {
@@ -158,6 +190,18 @@ pub fn create_projection_matrix(fov_y: f32, aspect: f32, near: f32, far: f32) ->
     )
 }
 
+/// Creates an orthographic projection matrix
+/// left, right, bottom, top: Extents of the view volume in view space
+/// near, far: Clipping plane distances
+pub fn create_orthographic_matrix(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Matrix {
+    new_matrix4(
+        2.0 / (right - left), 0.0, 0.0, -(right + left) / (right - left),
+        0.0, 2.0 / (top - bottom), 0.0, -(top + bottom) / (top - bottom),
+        0.0, 0.0, -2.0 / (far - near), -(far + near) / (far - near),
+        0.0, 0.0, 0.0, 1.0,
+    )
+}
+
 /// Creates a viewport matrix to transform NDC coordinates to screen space
 /// x, y: Viewport position (typically 0, 0)
 /// width, height: Viewport dimensions in pixels
@@ -194,4 +238,330 @@ pub fn multiply_matrix_matrix(a: &Matrix, b: &Matrix) -> Matrix {
         m14: a.m2*b.m12 + a.m6*b.m13 + a.m10*b.m14+ a.m14*b.m15,
         m15: a.m3*b.m12 + a.m7*b.m13 + a.m11*b.m14+ a.m15*b.m15,
     }
-}
\ No newline at end of file
+}
+/// Multiplies a point (w = 1), dividing by w when the matrix is projective
+pub fn multiply_matrix_point(matrix: &Matrix, p: Vector3) -> Vector3 {
+    let v = multiply_matrix_vector4(matrix, &Vector4::new(p.x, p.y, p.z, 1.0));
+    if v.w != 0.0 && v.w != 1.0 {
+        Vector3::new(v.x / v.w, v.y / v.w, v.z / v.w)
+    } else {
+        Vector3::new(v.x, v.y, v.z)
+    }
+}
+
+pub fn create_translation_matrix(translation: Vector3) -> Matrix {
+    new_matrix4(
+        1.0, 0.0, 0.0, translation.x,
+        0.0, 1.0, 0.0, translation.y,
+        0.0, 0.0, 1.0, translation.z,
+        0.0, 0.0, 0.0, 1.0,
+    )
+}
+
+pub fn create_scale_matrix(scale: Vector3) -> Matrix {
+    new_matrix3(
+        scale.x, 0.0, 0.0,
+        0.0, scale.y, 0.0,
+        0.0, 0.0, scale.z,
+    )
+}
+
+pub fn transpose_matrix(m: &Matrix) -> Matrix {
+    Matrix {
+        m0: m.m0,  m4: m.m1,  m8: m.m2,   m12: m.m3,
+        m1: m.m4,  m5: m.m5,  m9: m.m6,   m13: m.m7,
+        m2: m.m8,  m6: m.m9,  m10: m.m10, m14: m.m11,
+        m3: m.m12, m7: m.m13, m11: m.m14, m15: m.m15,
+    }
+}
+
+/// The 2x2 sub-determinants shared by `matrix_determinant` and `invert_matrix`
+/// (Laplace expansion along the first two columns against the last two)
+fn sub_determinants(m: &Matrix) -> ([f32; 6], [f32; 6]) {
+    let s = [
+        m.m0 * m.m5 - m.m1 * m.m4,
+        m.m0 * m.m6 - m.m2 * m.m4,
+        m.m0 * m.m7 - m.m3 * m.m4,
+        m.m1 * m.m6 - m.m2 * m.m5,
+        m.m1 * m.m7 - m.m3 * m.m5,
+        m.m2 * m.m7 - m.m3 * m.m6,
+    ];
+    let c = [
+        m.m8 * m.m13 - m.m9 * m.m12,
+        m.m8 * m.m14 - m.m10 * m.m12,
+        m.m8 * m.m15 - m.m11 * m.m12,
+        m.m9 * m.m14 - m.m10 * m.m13,
+        m.m9 * m.m15 - m.m11 * m.m13,
+        m.m10 * m.m15 - m.m11 * m.m14,
+    ];
+    (s, c)
+}
+
+pub fn matrix_determinant(m: &Matrix) -> f32 {
+    let (s, c) = sub_determinants(m);
+    s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0]
+}
+
+/// General 4x4 inverse; None when the matrix is singular
+pub fn invert_matrix(m: &Matrix) -> Option<Matrix> {
+    let (s, c) = sub_determinants(m);
+    let det = s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0];
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let inv = 1.0 / det;
+    Some(Matrix {
+        m0: ( m.m5 * c[5] - m.m6 * c[4] + m.m7 * c[3]) * inv,
+        m1: (-m.m1 * c[5] + m.m2 * c[4] - m.m3 * c[3]) * inv,
+        m2: ( m.m13 * s[5] - m.m14 * s[4] + m.m15 * s[3]) * inv,
+        m3: (-m.m9 * s[5] + m.m10 * s[4] - m.m11 * s[3]) * inv,
+
+        m4: (-m.m4 * c[5] + m.m6 * c[2] - m.m7 * c[1]) * inv,
+        m5: ( m.m0 * c[5] - m.m2 * c[2] + m.m3 * c[1]) * inv,
+        m6: (-m.m12 * s[5] + m.m14 * s[2] - m.m15 * s[1]) * inv,
+        m7: ( m.m8 * s[5] - m.m10 * s[2] + m.m11 * s[1]) * inv,
+
+        m8: ( m.m4 * c[4] - m.m5 * c[2] + m.m7 * c[0]) * inv,
+        m9: (-m.m0 * c[4] + m.m1 * c[2] - m.m3 * c[0]) * inv,
+        m10: ( m.m12 * s[4] - m.m13 * s[2] + m.m15 * s[0]) * inv,
+        m11: (-m.m8 * s[4] + m.m9 * s[2] - m.m11 * s[0]) * inv,
+
+        m12: (-m.m4 * c[3] + m.m5 * c[1] - m.m6 * c[0]) * inv,
+        m13: ( m.m0 * c[3] - m.m1 * c[1] + m.m2 * c[0]) * inv,
+        m14: (-m.m12 * s[3] + m.m13 * s[1] - m.m14 * s[0]) * inv,
+        m15: ( m.m8 * s[3] - m.m9 * s[1] + m.m10 * s[0]) * inv,
+    })
+}
+
+/// 3x3 matrix in row-major order (`m[row][col]`): the linear part of a transform,
+/// used for normals
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Matrix3 {
+    pub m: [[f32; 3]; 3],
+}
+
+impl Matrix3 {
+    pub fn identity() -> Self {
+        Matrix3 { m: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]] }
+    }
+
+    /// Upper-left 3x3 block of a 4x4 matrix
+    pub fn from_matrix4(m: &Matrix) -> Self {
+        Matrix3 {
+            m: [
+                [m.m0, m.m4, m.m8],
+                [m.m1, m.m5, m.m9],
+                [m.m2, m.m6, m.m10],
+            ],
+        }
+    }
+
+    pub fn transpose(&self) -> Self {
+        let m = &self.m;
+        Matrix3 {
+            m: [
+                [m[0][0], m[1][0], m[2][0]],
+                [m[0][1], m[1][1], m[2][1]],
+                [m[0][2], m[1][2], m[2][2]],
+            ],
+        }
+    }
+
+    pub fn determinant(&self) -> f32 {
+        let m = &self.m;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    /// Inverse by the adjugate; None when singular
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        let m = &self.m;
+        let inv = 1.0 / det;
+        let cofactor = |r0: usize, r1: usize, c0: usize, c1: usize| m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0];
+        Some(Matrix3 {
+            m: [
+                [cofactor(1, 2, 1, 2) * inv, -cofactor(0, 2, 1, 2) * inv, cofactor(0, 1, 1, 2) * inv],
+                [-cofactor(1, 2, 0, 2) * inv, cofactor(0, 2, 0, 2) * inv, -cofactor(0, 1, 0, 2) * inv],
+                [cofactor(1, 2, 0, 1) * inv, -cofactor(0, 2, 0, 1) * inv, cofactor(0, 1, 0, 1) * inv],
+            ],
+        })
+    }
+
+    pub fn multiply(&self, other: &Matrix3) -> Self {
+        let (a, b) = (&self.m, &other.m);
+        let mut m = [[0.0; 3]; 3];
+        for (r, row) in m.iter_mut().enumerate() {
+            for (c, value) in row.iter_mut().enumerate() {
+                *value = a[r][0] * b[0][c] + a[r][1] * b[1][c] + a[r][2] * b[2][c];
+            }
+        }
+        Matrix3 { m }
+    }
+
+    pub fn multiply_vector(&self, v: Vector3) -> Vector3 {
+        let m = &self.m;
+        Vector3::new(
+            m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z,
+            m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z,
+            m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z,
+        )
+    }
+}
+
+/// Transforms normals correctly under non-uniform scale: the inverse transpose of the
+/// model's linear part. Falls back to the linear part itself if it is singular.
+pub fn create_normal_matrix(model: &Matrix) -> Matrix3 {
+    let linear = Matrix3::from_matrix4(model);
+    linear.inverse().map(|inv| inv.transpose()).unwrap_or(linear)
+}
+
+/// View matrix looking from `eye` along `direction` (look-at without a target point)
+pub fn create_look_to_matrix(eye: Vector3, direction: Vector3, up: Vector3) -> Matrix {
+    create_view_matrix(eye, eye + direction, up)
+}
+
+/// Off-axis perspective frustum: the near-plane rectangle need not be centred on the
+/// view axis (stereo, tiled or multi-monitor renders)
+pub fn create_frustum_matrix(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Matrix {
+    new_matrix4(
+        2.0 * near / (right - left), 0.0, (right + left) / (right - left), 0.0,
+        0.0, 2.0 * near / (top - bottom), (top + bottom) / (top - bottom), 0.0,
+        0.0, 0.0, -(far + near) / (far - near), -(2.0 * far * near) / (far - near),
+        0.0, 0.0, -1.0, 0.0,
+    )
+}
+
+/// Screen to world: `screen` is (x, y) in pixels and z the NDC depth in [-1, 1], the
+/// same convention `transform` produces. None if the matrices are singular.
+pub fn unproject(screen: Vector3, view: &Matrix, projection: &Matrix, viewport: &Matrix) -> Option<Vector3> {
+    let ndc_xy = multiply_matrix_point(&invert_matrix(viewport)?, Vector3::new(screen.x, screen.y, 0.0));
+    let inverse = invert_matrix(&multiply_matrix_matrix(projection, view))?;
+    let world = multiply_matrix_vector4(&inverse, &Vector4::new(ndc_xy.x, ndc_xy.y, screen.z, 1.0));
+    if world.w.abs() < 1e-12 {
+        return None;
+    }
+    Some(Vector3::new(world.x / world.w, world.y / world.w, world.z / world.w))
+}
+
+/// World-space ray (origin on the near plane, unit direction) through a pixel
+pub fn screen_ray(x: f32, y: f32, view: &Matrix, projection: &Matrix, viewport: &Matrix) -> Option<(Vector3, Vector3)> {
+    let near = unproject(Vector3::new(x, y, -1.0), view, projection, viewport)?;
+    let far = unproject(Vector3::new(x, y, 1.0), view, projection, viewport)?;
+    Some((near, (far - near).normalized()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity() -> Matrix {
+        create_scale_matrix(Vector3::new(1.0, 1.0, 1.0))
+    }
+
+    fn entries(m: &Matrix) -> [f32; 16] {
+        [m.m0, m.m1, m.m2, m.m3, m.m4, m.m5, m.m6, m.m7, m.m8, m.m9, m.m10, m.m11, m.m12, m.m13, m.m14, m.m15]
+    }
+
+    fn assert_matrix_eq(a: &Matrix, b: &Matrix) {
+        for (x, y) in entries(a).into_iter().zip(entries(b)) {
+            assert!((x - y).abs() < 1e-4, "{:?} != {:?}", entries(a), entries(b));
+        }
+    }
+
+    fn assert_vector_eq(a: Vector3, b: Vector3) {
+        assert!((a - b).length() < 1e-3, "{a:?} != {b:?}");
+    }
+
+    /// Rows 1 2 3 4 / 5 6 7 8 / 2 6 4 8 / 3 1 1 2, determinant 72
+    fn known() -> Matrix {
+        new_matrix4(
+            1.0, 2.0, 3.0, 4.0,
+            5.0, 6.0, 7.0, 8.0,
+            2.0, 6.0, 4.0, 8.0,
+            3.0, 1.0, 1.0, 2.0,
+        )
+    }
+
+    #[test]
+    fn inverse_of_translate_rotate_scale() {
+        let t = create_translation_matrix(Vector3::new(3.0, -2.0, 5.0));
+        let r = create_rotation_matrix(Quat::from_euler(Vector3::new(0.3, -1.1, 0.7)));
+        let s = create_scale_matrix(Vector3::new(2.0, 0.5, 1.5));
+        let m = multiply_matrix_matrix(&t, &multiply_matrix_matrix(&r, &s));
+        let inverse = invert_matrix(&m).expect("T·R·S is invertible");
+        assert_matrix_eq(&multiply_matrix_matrix(&inverse, &m), &identity());
+        assert_matrix_eq(&multiply_matrix_matrix(&m, &inverse), &identity());
+        assert_matrix_eq(&multiply_matrix_matrix(&invert_matrix(&known()).unwrap(), &known()), &identity());
+    }
+
+    #[test]
+    fn singular_matrices_have_no_inverse() {
+        let flat = create_scale_matrix(Vector3::new(1.0, 0.0, 1.0));
+        assert!(invert_matrix(&flat).is_none());
+        // Third row twice the first
+        let dependent = new_matrix4(
+            1.0, 2.0, 3.0, 4.0,
+            0.0, 1.0, 0.0, 2.0,
+            2.0, 4.0, 6.0, 8.0,
+            1.0, 0.0, 1.0, 0.0,
+        );
+        assert!(invert_matrix(&dependent).is_none());
+        assert!(Matrix3::from_matrix4(&flat).inverse().is_none());
+    }
+
+    #[test]
+    fn determinant_and_transpose() {
+        assert!((matrix_determinant(&known()) - 72.0).abs() < 1e-3);
+        assert!((matrix_determinant(&transpose_matrix(&known())) - 72.0).abs() < 1e-3);
+        assert_matrix_eq(&transpose_matrix(&transpose_matrix(&known())), &known());
+        let t = transpose_matrix(&known());
+        assert_eq!((t.m4, t.m1), (known().m1, known().m4));
+        // Upper-left block 1 2 3 / 5 6 7 / 2 6 4
+        assert!((Matrix3::from_matrix4(&known()).determinant() - 24.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn matrix3_inverse() {
+        let m = Matrix3 { m: [[2.0, 0.0, 1.0], [1.0, 3.0, 0.0], [0.0, 1.0, 4.0]] };
+        let product = m.multiply(&m.inverse().unwrap());
+        for (r, row) in product.m.iter().enumerate() {
+            for (c, value) in row.iter().enumerate() {
+                assert!((value - if r == c { 1.0 } else { 0.0 }).abs() < 1e-5, "{product:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn normal_matrix_under_non_uniform_scale() {
+        let normal = create_normal_matrix(&create_scale_matrix(Vector3::new(1.0, 0.5, 1.0)));
+        let up = normal.multiply_vector(Vector3::new(0.0, 1.0, 0.0));
+        assert_vector_eq(up.normalized(), Vector3::new(0.0, 1.0, 0.0));
+        // A slanted normal stays perpendicular to its squashed tangent
+        let model = create_scale_matrix(Vector3::new(1.0, 0.5, 1.0));
+        let tangent = multiply_matrix_direction(&model, Vector3::new(1.0, -1.0, 0.0));
+        let slanted = normal.multiply_vector(Vector3::new(1.0, 1.0, 0.0));
+        assert!(slanted.dot(tangent).abs() < 1e-5);
+    }
+
+    #[test]
+    fn unproject_inverts_transform() {
+        let view = create_view_matrix(Vector3::new(4.0, 3.0, 12.0), Vector3::new(0.0, 0.5, 0.0), Vector3::new(0.0, 1.0, 0.0));
+        let projection = create_projection_matrix(60f32.to_radians(), 800.0 / 600.0, 0.1, 100.0);
+        let viewport = create_viewport_matrix(0.0, 0.0, 800.0, 600.0);
+        for world in [Vector3::new(0.0, 0.0, 0.0), Vector3::new(1.5, -0.7, 2.0), Vector3::new(-3.0, 2.0, -4.0)] {
+            let screen = crate::transform(world, &view, &projection, &viewport).expect("point in front of the camera");
+            let back = unproject(screen, &view, &projection, &viewport).unwrap();
+            assert_vector_eq(back, world);
+
+            // The ray through that pixel passes through the point
+            let (origin, direction) = screen_ray(screen.x, screen.y, &view, &projection, &viewport).unwrap();
+            let along = (world - origin).dot(direction);
+            assert_vector_eq(origin + direction * along, world);
+        }
+    }
+}
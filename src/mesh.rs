@@ -0,0 +1,24 @@
+#![allow(dead_code)]
+
+use raylib::prelude::*;
+use std::path::Path;
+
+/// Loads every model in an OBJ file as a flat triangle list (three vertices per
+/// triangle, the layout `render` expects). Faces are triangulated; materials are ignored.
+pub fn load_obj(path: &Path) -> Result<Vec<Vector3>, String> {
+    let (models, _materials) = tobj::load_obj(path, &tobj::GPU_LOAD_OPTIONS)
+        .map_err(|e| format!("no se puede cargar '{}': {e}", path.display()))?;
+
+    let mut vertices = Vec::new();
+    for model in &models {
+        let mesh = &model.mesh;
+        for &index in &mesh.indices {
+            let i = index as usize * 3;
+            vertices.push(Vector3::new(mesh.positions[i], mesh.positions[i + 1], mesh.positions[i + 2]));
+        }
+    }
+    if vertices.is_empty() {
+        return Err(format!("'{}' no tiene triángulos", path.display()));
+    }
+    Ok(vertices)
+}
@@ -0,0 +1,388 @@
+#![allow(dead_code)]
+
+use raylib::prelude::*;
+use crate::entity::Entity;
+use crate::scene::Scene;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Integrator {
+    VelocityVerlet, // symplectic, 1 force evaluation per step; energy oscillates but does not drift
+    Rk4,            // 4 force evaluations per step; more accurate short-term, slow energy drift
+}
+
+impl Integrator {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Integrator::VelocityVerlet => "velocity-verlet",
+            Integrator::Rk4 => "rk4",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Integrator::VelocityVerlet => Integrator::Rk4,
+            Integrator::Rk4 => Integrator::VelocityVerlet,
+        }
+    }
+}
+
+/// A massive entity under simulation. `mass` is really G·m (G = 1 in scene units).
+#[derive(Clone, Debug)]
+pub struct Body {
+    pub name: String,
+    pub mass: f32,
+    pub radius: f32,
+    pub position: Vector3,
+    pub velocity: Vector3,
+}
+
+/// Two bodies that touched and were merged into `survivor`
+#[derive(Clone, Debug)]
+pub struct Merger {
+    pub survivor: String,
+    pub absorbed: String,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Diagnostics {
+    pub kinetic: f32,
+    pub potential: f32,
+    pub total: f32,
+    pub momentum: Vector3,
+    pub angular_momentum: Vector3, // about the world origin
+    pub energy_drift: f32,         // (E - E0) / |E0| since the simulation started
+}
+
+pub struct NBodySim {
+    pub bodies: Vec<Body>,
+    pub integrator: Integrator,
+    pub substeps: u32,
+    pub softening: f32,            // Plummer softening length, avoids singular forces at close range
+    pub merge_on_collision: bool,
+    initial_energy: f32,
+    accelerations: Vec<Vector3>,   // cached a(t) for velocity Verlet
+    scales: Vec<(String, Vector3)>, // each body's scale before any merger grew it
+    absorbed: Vec<(usize, Entity)>, // entities removed by mergers, with their index in the scene
+    adopted: Vec<(String, String)>, // children of absorbed bodies moved to the survivor: (child, original parent)
+}
+
+impl NBodySim {
+    /// Takes over every entity with mass > 0, starting from its current position and
+    /// Keplerian velocity at `time`. The system is moved to its barycentric frame so it
+    /// doesn't drift off screen.
+    pub fn from_scene(scene: &Scene, time: f32, integrator: Integrator, substeps: u32) -> Self {
+        let scales = scene.entities.iter()
+            .filter(|e| e.mass > 0.0)
+            .map(|e| (e.name.clone(), e.transform.scale))
+            .collect();
+        let bodies = scene.entities.iter().enumerate()
+            .filter(|(_, e)| e.mass > 0.0)
+            .map(|(i, e)| Body {
+                name: e.name.clone(),
+                mass: e.mass,
+                radius: e.bounding_radius(),
+                position: e.world_position(),
+                velocity: scene.orbit_velocity(i, time),
+            })
+            .collect();
+
+        let mut sim = NBodySim {
+            bodies,
+            integrator,
+            substeps: substeps.max(1),
+            softening: 0.05,
+            merge_on_collision: true,
+            initial_energy: 0.0,
+            accelerations: Vec::new(),
+            scales,
+            absorbed: Vec::new(),
+            adopted: Vec::new(),
+        };
+        sim.zero_momentum();
+        let (kinetic, potential) = sim.energy();
+        sim.initial_energy = kinetic + potential;
+        sim
+    }
+
+    /// Removes the centre-of-mass velocity from every body
+    pub fn zero_momentum(&mut self) {
+        let total_mass: f32 = self.bodies.iter().map(|b| b.mass).sum();
+        if total_mass <= 0.0 {
+            return;
+        }
+        let momentum = self.bodies.iter().fold(Vector3::zero(), |acc, b| acc + b.velocity * b.mass);
+        let drift = momentum / total_mass;
+        for b in &mut self.bodies {
+            b.velocity -= drift;
+        }
+        self.accelerations.clear();
+    }
+
+    fn acceleration_at(&self, positions: &[Vector3]) -> Vec<Vector3> {
+        let eps2 = self.softening * self.softening;
+        let mut acc = vec![Vector3::zero(); positions.len()];
+        for i in 0..positions.len() {
+            for j in (i + 1)..positions.len() {
+                let d = positions[j] - positions[i];
+                let r2 = d.dot(d) + eps2;
+                let inv_r3 = 1.0 / (r2 * r2.sqrt());
+                acc[i] += d * (self.bodies[j].mass * inv_r3);
+                acc[j] -= d * (self.bodies[i].mass * inv_r3);
+            }
+        }
+        acc
+    }
+
+    fn positions(&self) -> Vec<Vector3> {
+        self.bodies.iter().map(|b| b.position).collect()
+    }
+
+    fn step_verlet(&mut self, dt: f32) {
+        if self.accelerations.len() != self.bodies.len() {
+            self.accelerations = self.acceleration_at(&self.positions());
+        }
+        for (b, a) in self.bodies.iter_mut().zip(&self.accelerations) {
+            b.position += b.velocity * dt + *a * (0.5 * dt * dt);
+        }
+        let next = self.acceleration_at(&self.positions());
+        for ((b, a0), a1) in self.bodies.iter_mut().zip(&self.accelerations).zip(&next) {
+            b.velocity += (*a0 + *a1) * (0.5 * dt);
+        }
+        self.accelerations = next;
+    }
+
+    fn step_rk4(&mut self, dt: f32) {
+        let x0 = self.positions();
+        let v0: Vec<Vector3> = self.bodies.iter().map(|b| b.velocity).collect();
+        let offset = |base: &[Vector3], d: &[Vector3], h: f32| -> Vec<Vector3> {
+            base.iter().zip(d).map(|(b, d)| *b + *d * h).collect()
+        };
+
+        // Derivatives: dx/dt = v, dv/dt = a(x)
+        let k1x = v0.clone();
+        let k1v = self.acceleration_at(&x0);
+        let k2x = offset(&v0, &k1v, dt * 0.5);
+        let k2v = self.acceleration_at(&offset(&x0, &k1x, dt * 0.5));
+        let k3x = offset(&v0, &k2v, dt * 0.5);
+        let k3v = self.acceleration_at(&offset(&x0, &k2x, dt * 0.5));
+        let k4x = offset(&v0, &k3v, dt);
+        let k4v = self.acceleration_at(&offset(&x0, &k3x, dt));
+
+        for (i, b) in self.bodies.iter_mut().enumerate() {
+            b.position += (k1x[i] + k2x[i] * 2.0 + k3x[i] * 2.0 + k4x[i]) * (dt / 6.0);
+            b.velocity += (k1v[i] + k2v[i] * 2.0 + k3v[i] * 2.0 + k4v[i]) * (dt / 6.0);
+        }
+        self.accelerations.clear();
+    }
+
+    /// Advances the simulation by `dt` seconds in `substeps` equal steps, merging
+    /// bodies that touch. Negative `dt` integrates backwards.
+    pub fn step(&mut self, dt: f32) -> Vec<Merger> {
+        let mut mergers = Vec::new();
+        if dt == 0.0 || self.bodies.is_empty() {
+            return mergers;
+        }
+        let h = dt / self.substeps as f32;
+        for _ in 0..self.substeps {
+            match self.integrator {
+                Integrator::VelocityVerlet => self.step_verlet(h),
+                Integrator::Rk4 => self.step_rk4(h),
+            }
+            if self.merge_on_collision {
+                mergers.extend(self.resolve_collisions());
+            }
+        }
+        mergers
+    }
+
+    /// Perfectly inelastic merging: mass and momentum are conserved, the merged body
+    /// sits at the centre of mass and keeps the combined volume.
+    fn resolve_collisions(&mut self) -> Vec<Merger> {
+        let mut mergers = Vec::new();
+        let mut i = 0;
+        while i < self.bodies.len() {
+            let mut j = i + 1;
+            while j < self.bodies.len() {
+                let (a, b) = (self.bodies[i].clone(), self.bodies[j].clone());
+                if (a.position - b.position).length() < a.radius + b.radius {
+                    let (big, small) = if a.mass >= b.mass { (&a, &b) } else { (&b, &a) };
+                    let mass = a.mass + b.mass;
+                    let merger = Merger { survivor: big.name.clone(), absorbed: small.name.clone() };
+                    self.bodies[i] = Body {
+                        name: big.name.clone(),
+                        mass,
+                        radius: (a.radius.powi(3) + b.radius.powi(3)).cbrt(),
+                        position: (a.position * a.mass + b.position * b.mass) / mass,
+                        velocity: (a.velocity * a.mass + b.velocity * b.mass) / mass,
+                    };
+                    self.bodies.remove(j);
+                    self.accelerations.clear();
+                    mergers.push(merger);
+                    // The grown body may now touch one already checked
+                    j = i + 1;
+                    continue;
+                }
+                j += 1;
+            }
+            i += 1;
+        }
+        mergers
+    }
+
+    /// (kinetic, potential) energy, with the same softening as the force
+    fn energy(&self) -> (f32, f32) {
+        let eps2 = self.softening * self.softening;
+        let kinetic = self.bodies.iter().map(|b| 0.5 * b.mass * b.velocity.dot(b.velocity)).sum();
+        let mut potential = 0.0;
+        for i in 0..self.bodies.len() {
+            for j in (i + 1)..self.bodies.len() {
+                let d = self.bodies[j].position - self.bodies[i].position;
+                potential -= self.bodies[i].mass * self.bodies[j].mass / (d.dot(d) + eps2).sqrt();
+            }
+        }
+        (kinetic, potential)
+    }
+
+    pub fn diagnostics(&self) -> Diagnostics {
+        let (kinetic, potential) = self.energy();
+        let total = kinetic + potential;
+        let momentum = self.bodies.iter().fold(Vector3::zero(), |acc, b| acc + b.velocity * b.mass);
+        let angular_momentum = self.bodies.iter()
+            .fold(Vector3::zero(), |acc, b| acc + b.position.cross(b.velocity * b.mass));
+        let energy_drift = if self.initial_energy != 0.0 {
+            (total - self.initial_energy) / self.initial_energy.abs()
+        } else {
+            0.0
+        };
+        Diagnostics { kinetic, potential, total, momentum, angular_momentum, energy_drift }
+    }
+
+    /// Writes simulated positions back into the scene. Entities absorbed by a merger are
+    /// taken out of the scene (kept for `release`) and their children follow the survivor;
+    /// survivors are rescaled to their new radius.
+    pub fn apply(&mut self, scene: &mut Scene, mergers: &[Merger]) {
+        for m in mergers {
+            if let Some(i) = scene.entities.iter().position(|e| e.name == m.absorbed) {
+                self.absorbed.push((i, scene.entities.remove(i)));
+            }
+            for e in &mut scene.entities {
+                if e.parent.as_deref() == Some(m.absorbed.as_str()) {
+                    self.adopted.push((e.name.clone(), m.absorbed.clone()));
+                    e.parent = Some(m.survivor.clone());
+                }
+            }
+        }
+        for b in &self.bodies {
+            if let Some(e) = scene.entities.iter_mut().find(|e| e.name == b.name) {
+                e.transform.translation = b.position;
+                e.simulated = true;
+                let radius = e.bounding_radius();
+                if radius > 0.0 && (b.radius - radius).abs() > 1e-4 {
+                    e.transform.scale *= b.radius / radius;
+                }
+            }
+        }
+    }
+
+    /// Hands the simulated entities back to their Keplerian orbits and undoes the mergers:
+    /// absorbed entities return, survivors get their size back and adopted children their
+    /// parent
+    pub fn release(self, scene: &mut Scene) {
+        for (i, mut e) in self.absorbed.into_iter().rev() {
+            e.world.dirty = true;
+            scene.entities.insert(i.min(scene.entities.len()), e);
+        }
+        for e in &mut scene.entities {
+            e.simulated = false;
+            if let Some((_, scale)) = self.scales.iter().find(|(name, _)| *name == e.name) {
+                e.transform.scale = *scale;
+            }
+            // The first entry holds the parent from before any merger
+            if let Some((_, parent)) = self.adopted.iter().find(|(name, _)| *name == e.name) {
+                e.parent = Some(parent.clone());
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "E = {:.4} (K {:.4}, U {:.4}, deriva {:+.2e})  |p| = {:.2e}  |L| = {:.4}",
+            self.total, self.kinetic, self.potential, self.energy_drift,
+            self.momentum.length(), self.angular_momentum.length(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(name: &str, mass: f32, radius: f32, position: Vector3, velocity: Vector3) -> Body {
+        Body { name: name.to_string(), mass, radius, position, velocity }
+    }
+
+    fn sim(bodies: Vec<Body>, integrator: Integrator) -> NBodySim {
+        let mut sim = NBodySim {
+            bodies,
+            integrator,
+            substeps: 1,
+            softening: 0.0,
+            merge_on_collision: true,
+            initial_energy: 0.0,
+            accelerations: Vec::new(),
+            scales: Vec::new(),
+            absorbed: Vec::new(),
+            adopted: Vec::new(),
+        };
+        sim.zero_momentum();
+        let (kinetic, potential) = sim.energy();
+        sim.initial_energy = kinetic + potential;
+        sim
+    }
+
+    /// A light body on a circular orbit of radius 5 around a heavy one
+    fn two_body_circle(integrator: Integrator) -> NBodySim {
+        let (big, small, r): (f32, f32, f32) = (1.0, 0.01, 5.0);
+        let speed = ((big + small) / r).sqrt();
+        sim(vec![
+            body("star", big, 0.5, Vector3::zero(), Vector3::zero()),
+            body("planet", small, 0.1, Vector3::new(r, 0.0, 0.0), Vector3::new(0.0, 0.0, -speed)),
+        ], integrator)
+    }
+
+    #[test]
+    fn circular_orbit_keeps_its_energy() {
+        for integrator in [Integrator::VelocityVerlet, Integrator::Rk4] {
+            let mut sim = two_body_circle(integrator);
+            // About two periods (T = 2π·√(r³/M) ≈ 70 s)
+            for _ in 0..2800 {
+                assert!(sim.step(0.05).is_empty());
+            }
+            let drift = sim.diagnostics().energy_drift;
+            assert!(drift.abs() < 1e-4, "{}: energy drift {drift}", integrator.name());
+            let r = (sim.bodies[1].position - sim.bodies[0].position).length();
+            assert!((r - 5.0).abs() < 0.01, "{}: radius {r}", integrator.name());
+        }
+    }
+
+    #[test]
+    fn merger_conserves_mass_and_momentum() {
+        let mut sim = sim(vec![
+            body("a", 2.0, 0.5, Vector3::new(-0.3, 0.0, 0.0), Vector3::new(1.0, 0.5, 0.0)),
+            body("b", 0.5, 0.2, Vector3::new(0.3, 0.0, 0.0), Vector3::new(-2.0, 0.0, 1.0)),
+            body("c", 0.1, 0.1, Vector3::new(0.0, 50.0, 0.0), Vector3::new(0.0, 0.0, 0.3)),
+        ], Integrator::VelocityVerlet);
+        let before = sim.diagnostics().momentum;
+        let mergers = sim.step(1e-3);
+        assert_eq!(mergers.len(), 1);
+        assert_eq!((mergers[0].survivor.as_str(), mergers[0].absorbed.as_str()), ("a", "b"));
+        assert_eq!(sim.bodies.len(), 2);
+        assert!((sim.bodies[0].mass - 2.5).abs() < 1e-6);
+        assert!((sim.bodies[0].radius - (0.5f32.powi(3) + 0.2f32.powi(3)).cbrt()).abs() < 1e-6);
+        let after = sim.diagnostics().momentum;
+        assert!((after - before).length() < 1e-5, "momentum {before:?} -> {after:?}");
+    }
+}
@@ -0,0 +1,123 @@
+#![allow(dead_code)]
+
+use raylib::prelude::*;
+use std::f32::consts::{PI, TAU};
+
+/// Classical Keplerian elements. Angles in radians, `period` in seconds of simulation time.
+/// The reference plane is XZ (Y up); positions are relative to the focus (the parent body).
+#[derive(Clone, Copy, Debug)]
+pub struct OrbitalElements {
+    pub semi_major_axis: f32,
+    pub eccentricity: f32,
+    pub inclination: f32,
+    pub ascending_node: f32,        // longitude of the ascending node (Ω)
+    pub argument_of_periapsis: f32, // ω
+    pub mean_anomaly_at_epoch: f32, // M0, at t = 0
+    pub period: f32,
+}
+
+/// Orbit of an entity around its scene-graph parent (or the world origin without one).
+/// The elements are in world axes and units: only the parent's position is inherited.
+#[derive(Clone, Debug)]
+pub struct Orbit {
+    pub elements: OrbitalElements,
+}
+
+impl OrbitalElements {
+    /// Circular orbit in the reference plane
+    pub fn circular(radius: f32, period: f32) -> Self {
+        OrbitalElements {
+            semi_major_axis: radius,
+            eccentricity: 0.0,
+            inclination: 0.0,
+            ascending_node: 0.0,
+            argument_of_periapsis: 0.0,
+            mean_anomaly_at_epoch: 0.0,
+            period,
+        }
+    }
+
+    pub fn mean_anomaly(&self, time: f32) -> f32 {
+        if self.period == 0.0 {
+            return self.mean_anomaly_at_epoch;
+        }
+        (self.mean_anomaly_at_epoch + TAU * time / self.period).rem_euclid(TAU)
+    }
+
+    /// Position relative to the focus at `time`
+    pub fn position(&self, time: f32) -> Vector3 {
+        let e = self.eccentricity.clamp(0.0, 0.99);
+        let ecc_anomaly = solve_kepler(self.mean_anomaly(time), e);
+
+        // True anomaly and distance from the focus
+        let (s, c) = (ecc_anomaly * 0.5).sin_cos();
+        let nu = 2.0 * ((1.0 + e).sqrt() * s).atan2((1.0 - e).sqrt() * c);
+        let r = self.semi_major_axis * (1.0 - e * ecc_anomaly.cos());
+
+        // Perifocal -> reference frame (Z up), then Z-up -> Y-up
+        let (so, co) = self.ascending_node.sin_cos();
+        let (si, ci) = self.inclination.sin_cos();
+        let (su, cu) = (self.argument_of_periapsis + nu).sin_cos();
+        let x = r * (co * cu - so * su * ci);
+        let y = r * (so * cu + co * su * ci);
+        let z = r * (su * si);
+        Vector3::new(x, z, -y)
+    }
+
+    /// Velocity relative to the focus (central difference), in units per second
+    pub fn velocity(&self, time: f32) -> Vector3 {
+        let h = (self.period.abs() * 1e-4).max(1e-4);
+        (self.position(time + h) - self.position(time - h)) / (2.0 * h)
+    }
+}
+
+/// Solves Kepler's equation M = E - e·sin(E) for the eccentric anomaly E (Newton-Raphson)
+pub fn solve_kepler(mean_anomaly: f32, eccentricity: f32) -> f32 {
+    let m = mean_anomaly.rem_euclid(TAU);
+    let mut ecc = if eccentricity < 0.8 { m } else { PI };
+    for _ in 0..16 {
+        let f = ecc - eccentricity * ecc.sin() - m;
+        let df = 1.0 - eccentricity * ecc.cos();
+        let step = f / df;
+        ecc -= step;
+        if step.abs() < 1e-6 {
+            break;
+        }
+    }
+    ecc
+}
+
+/// Period of an orbit with the given semi-major axis around a body of gravitational
+/// parameter `gm` (Kepler's third law), so orbits agree with the N-body mode
+pub fn kepler_period(semi_major_axis: f32, gm: f32) -> f32 {
+    TAU * (semi_major_axis.powi(3) / gm.max(1e-6)).sqrt()
+}
+
+/// Yaw (rotation about Y) that points an object's local +X along `velocity`
+pub fn tangent_yaw(velocity: Vector3) -> f32 {
+    (-velocity.z).atan2(velocity.x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kepler_solution_satisfies_the_equation() {
+        let anomalies = [0.0, 1e-4, 0.3, 1.0, 2.0, PI - 1e-3, PI, PI + 1e-3, 4.5, TAU - 1e-4, -0.5, 7.0];
+        for eccentricity in [0.0, 0.5, 0.95] {
+            for mean_anomaly in anomalies {
+                let ecc = solve_kepler(mean_anomaly, eccentricity);
+                let residual = ecc - eccentricity * ecc.sin() - mean_anomaly.rem_euclid(TAU);
+                assert!(residual.abs() < 1e-5, "e = {eccentricity}, M = {mean_anomaly}: E = {ecc}, residual {residual}");
+            }
+        }
+    }
+
+    #[test]
+    fn circular_orbit_is_solved_exactly() {
+        for mean_anomaly in [0.0, 1.0, PI, 5.0] {
+            assert!((solve_kepler(mean_anomaly, 0.0) - mean_anomaly).abs() < 1e-6);
+        }
+    }
+}
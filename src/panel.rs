@@ -0,0 +1,255 @@
+#![allow(dead_code)]
+
+use raylib::prelude::*;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use crate::framebuffer::Framebuffer;
+use crate::gradient::{GradientName, Gradients};
+use crate::hud::Hud;
+use crate::params::{ParamValue, Params};
+use crate::ring::RingMaterial;
+use crate::scene::Scene;
+use crate::scene_file::{material_source, ring_params_source};
+use crate::sun_presets::SunPresets;
+use crate::ui::{Ui, UiState};
+use crate::{FragmentShader, VertexShader};
+
+/// Panel width in glyph columns: label, slider and margins
+const COLUMNS: i32 = 36;
+
+/// In-window editor for the material parameters of one entity at a time (F2). Edits
+/// apply live; "Restablecer" goes back to the values the scene was loaded with (or the
+/// sun preset chosen since) and "Guardar" writes them as preset files.
+pub struct ParamPanel {
+    pub visible: bool,
+    pub entity: Option<String>, // entity shown, by name so it survives a scene reload
+    pub dir: PathBuf,           // where presets are saved
+    ui: UiState,
+    defaults: BTreeMap<String, (FragmentShader, Option<RingMaterial>)>,
+}
+
+impl ParamPanel {
+    pub fn new(dir: &Path) -> Self {
+        ParamPanel { visible: false, entity: None, dir: dir.to_path_buf(), ui: UiState::default(), defaults: BTreeMap::new() }
+    }
+
+    /// Remembers the materials as they are now; "Restablecer" brings them back. Call
+    /// after every scene (re)load and sun preset change.
+    pub fn remember(&mut self, scene: &Scene) {
+        self.defaults = scene.entities.iter()
+            .map(|e| (e.name.clone(), (e.fshader, e.rings.as_ref().map(|r| r.material))))
+            .collect();
+    }
+
+    /// Samples the mouse; true while the panel owns it (see `UiState::capture`)
+    pub fn capture(&mut self, window: &RaylibHandle) -> bool {
+        if !self.visible {
+            self.ui.release();
+            return false;
+        }
+        self.ui.capture(window)
+    }
+
+    /// Lays out and draws the panel on the right edge of the framebuffer, at the HUD's
+    /// text size, applying whatever the mouse changed to the scene
+    pub fn draw(&mut self, framebuffer: &mut Framebuffer, scene: &mut Scene, gradients: &mut Gradients, sun_presets: &mut SunPresets) {
+        if !self.visible || scene.entities.is_empty() {
+            return;
+        }
+        let count = scene.entities.len();
+        let mut index = self.entity.as_deref()
+            .and_then(|name| scene.entities.iter().position(|e| e.name == name))
+            .unwrap_or(0);
+
+        let scale = Hud::scale(framebuffer);
+        let width = COLUMNS * crate::font::GLYPH_WIDTH * scale;
+        let x = framebuffer.width as i32 - width - 4 * scale;
+        let mut ui = self.ui.begin(framebuffer, x, 4 * scale, width, scale);
+        ui.heading("Parámetros de material (F2)");
+        let step = ui.stepper(&format!("{} ({}/{count})", scene.entities[index].name, index + 1));
+        index = (index as i32 + step).rem_euclid(count as i32) as usize;
+
+        let entity = &mut scene.entities[index];
+        self.entity = Some(entity.name.clone());
+        ui.label(&format!("material: {}", material_kind(&entity.fshader)));
+        let action = ui.buttons(&["Restablecer", "Guardar"]);
+
+        let defaults = self.defaults.get(&entity.name).copied();
+        edit(&mut ui, &mut entity.fshader, defaults.map(|(shader, _)| shader.params()), gradients);
+        if let Some(rings) = &mut entity.rings {
+            ui.heading("anillos");
+            // Edit a copy: the ring mesh is regenerated if the radii moved
+            let mut material = rings.material;
+            edit(&mut ui, &mut material, defaults.and_then(|(_, rings)| rings).map(|m| m.params()), gradients);
+            rings.set_material(material);
+        }
+        ui.end();
+
+        match action {
+            Some(0) => match defaults {
+                Some((shader, rings)) => {
+                    entity.fshader = shader;
+                    if let (Some(r), Some(material)) = (&mut entity.rings, rings) {
+                        r.set_material(material);
+                    }
+                    println!("parámetros de '{}' restablecidos", entity.name);
+                }
+                None => println!("'{}' no tiene valores guardados para restablecer", entity.name),
+            },
+            Some(_) => match save_presets(&self.dir, &entity.name, &entity.fshader, entity.rings.as_ref().map(|r| &r.material)) {
+                Ok(files) => {
+                    // A saved sun joins the B cycle right away, not only on the next start
+                    if let FragmentShader::Solar(material) = entity.fshader {
+                        sun_presets.add(&preset_stem(&entity.name), material);
+                    }
+                    println!("preset guardado: {}", files.join(", "));
+                }
+                Err(error) => eprintln!("{error}"),
+            },
+            None => {}
+        }
+
+        // The flare vertex shader carries its own copy of the sun material
+        if let (FragmentShader::Solar(material), VertexShader::SolarFlare(_)) = (entity.fshader, entity.vshader) {
+            entity.vshader = VertexShader::SolarFlare(material);
+        }
+    }
+}
+
+fn material_kind(shader: &FragmentShader) -> &'static str {
+    match shader {
+        FragmentShader::Solar(_) => "Solar",
+        FragmentShader::Lit { .. } => "Lit",
+        FragmentShader::Planet(_) => "Planet",
+        FragmentShader::Ring(_) => "Ring",
+        FragmentShader::Glow { .. } => "Glow",
+    }
+}
+
+/// One widget per parameter: slider, integer slider, checkbox, colour picker or gradient
+/// chooser and editor. The loaded values (`defaults`) set the slider ranges, so they do
+/// not move while dragging.
+fn edit(ui: &mut Ui, material: &mut impl Params, defaults: Option<Vec<(&'static str, ParamValue)>>, gradients: &mut Gradients) {
+    let loaded = |name: &str| defaults.as_ref().and_then(|d| d.iter().find(|(n, _)| *n == name)).map(|(_, v)| *v);
+    for (name, value) in material.params() {
+        let changed = match value {
+            ParamValue::Float(mut v) => {
+                let (min, max) = range(name, loaded(name).and_then(|d| d.as_f32()).unwrap_or(v));
+                ui.slider(name, &mut v, min, max).then_some(ParamValue::Float(v))
+            }
+            ParamValue::Int(v) => {
+                let mut v = v.max(0) as u32;
+                let max = (2 * loaded(name).and_then(|d| d.as_u32()).unwrap_or(v)).max(8);
+                ui.slider_int(name, &mut v, 1, max).then_some(ParamValue::Int(v as i64))
+            }
+            ParamValue::Bool(mut v) => ui.checkbox(name, &mut v).then_some(ParamValue::Bool(v)),
+            ParamValue::Color([r, g, b]) => {
+                let mut v = Vector3::new(r, g, b);
+                ui.color(name, &mut v).then_some(ParamValue::Color([v.x, v.y, v.z]))
+            }
+            ParamValue::Gradient(ramp) => {
+                let names = gradients.names();
+                let step = ui.stepper(&format!("{name}: {ramp}"));
+                let chosen = (step != 0 && !names.is_empty()).then(|| {
+                    let current = names.iter().position(|&n| n == ramp).map_or(-step.min(0), |i| i as i32 + step);
+                    names[current.rem_euclid(names.len() as i32) as usize]
+                });
+                edit_gradient(ui, gradients, chosen.unwrap_or(ramp));
+                chosen.map(ParamValue::Gradient)
+            }
+        };
+        if let Some(value) = changed {
+            // The widget only produces values of the parameter's own kind
+            let _ = material.set_param(name, value);
+        }
+    }
+}
+
+/// Stops of a library gradient, edited in place so every material using it follows:
+/// position (between its neighbours) and colour of each, adding and removing stops,
+/// the colour space, and saving it back to the gradient folder
+fn edit_gradient(ui: &mut Ui, gradients: &mut Gradients, name: GradientName) {
+    let Some(gradient) = gradients.get_mut(name) else {
+        ui.label(&format!("'{name}' no existe: se usa 'temperatura'"));
+        return;
+    };
+    ui.ramp("  rampa", |t| gradient.sample(t));
+    let count = gradient.stops.len();
+    for i in 0..count {
+        let low = if i == 0 { 0.0 } else { gradient.stops[i - 1].position };
+        let high = if i + 1 == count { 1.0 } else { gradient.stops[i + 1].position };
+        let stop = &mut gradient.stops[i];
+        if ui.slider(&format!("  parada {}", i + 1), &mut stop.position, low, high.max(low + 1e-3)) {
+            stop.position = stop.position.clamp(low, high);
+        }
+        ui.color(&format!("  color {}", i + 1), &mut stop.color);
+    }
+    match ui.buttons(&["+ parada", "- parada", gradient.space.name()]) {
+        // A new stop splits the widest gap
+        Some(0) => {
+            let widest = gradient.stops.windows(2)
+                .max_by(|a, b| (a[1].position - a[0].position).total_cmp(&(b[1].position - b[0].position)))
+                .map(|pair| (pair[0].position + pair[1].position) / 2.0);
+            let at = widest.unwrap_or(if gradient.stops[0].position < 0.5 { 1.0 } else { 0.0 });
+            gradient.insert_stop(at);
+        }
+        Some(1) => gradient.remove_stop(count - 1),
+        Some(_) => gradient.space = gradient.space.next(),
+        None => {}
+    }
+    if ui.buttons(&["Guardar rampa"]).is_some() {
+        match gradients.save(name) {
+            Ok(path) => println!("gradiente guardado: {}", path.display()),
+            Err(error) => eprintln!("{error}"),
+        }
+    }
+}
+
+/// Slider range: fractions are 0 … 1, the rest spans twice the loaded value
+fn range(name: &str, loaded: f32) -> (f32, f32) {
+    match name {
+        "sea_level" => (-1.0, 1.0),
+        // A zero period would wrap the turbulence time modulo 0
+        "loop_period" => (0.1, (2.0 * loaded).max(1.0)),
+        "temperature" | "ridged" | "polar_cap" | "cloud_coverage" | "opacity" | "gap_threshold"
+        | "turbulence_gain" | "color_range" | "color_offset" | "core_weight" | "turbulence_weight" | "temperature_weight" => (0.0, 1.0),
+        _ if loaded < 0.0 => (2.0 * loaded, -2.0 * loaded),
+        _ => (0.0, (2.0 * loaded).max(1.0)),
+    }
+}
+
+/// File stem for an entity's presets: its name with anything but letters, digits and `-` as `_`
+fn preset_stem(entity: &str) -> String {
+    entity.chars().map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' }).collect()
+}
+
+/// Writes `<dir>/<entity>.ron`, a material file (`<dir>/sol/<entity>.ron` for a Solar
+/// one, where `SunPresets` looks), and for ringed entities `<dir>/<entity>_anillos.ron`
+/// with the `params` map of the `rings` block. Returns the files written.
+fn save_presets(dir: &Path, entity: &str, shader: &FragmentShader, rings: Option<&RingMaterial>) -> Result<Vec<String>, String> {
+    let stem = preset_stem(entity);
+    let mut files = Vec::new();
+    let mut write = |dir: &Path, name: String, text: String| {
+        std::fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+        let path = dir.join(name);
+        std::fs::write(&path, text).map_err(|e| format!("{}: {e}", path.display()))?;
+        files.push(path.display().to_string());
+        Ok::<(), String>(())
+    };
+    if let Some(source) = material_source(shader) {
+        if let FragmentShader::Solar(_) = shader {
+            write(&dir.join("sol"), format!("{stem}.ron"), format!(
+                "// Sol de '{entity}', guardado desde el panel de parámetros: un preset más\n// de la tecla B y de `--sun-preset {stem}`.\n{source}\n"
+            ))?;
+        } else {
+            write(dir, format!("{stem}.ron"), format!("// Material de '{entity}', guardado desde el panel de parámetros.\n{source}\n"))?;
+        }
+    }
+    if let Some(material) = rings {
+        write(dir, format!("{stem}_anillos.ron"), format!(
+            "// Anillos de '{entity}', guardados desde el panel de parámetros: el mapa\n// `params` del bloque `rings` de la escena.\n{}\n",
+            ring_params_source(material),
+        ))?;
+    }
+    Ok(files)
+}
@@ -0,0 +1,131 @@
+#![allow(dead_code)]
+
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::gradient::GradientName;
+
+/// Value of a named material parameter, as written in scene and preset files:
+/// `true`, `6`, `0.5`, `(0.2, 0.4, 1.0)` or a gradient name such as `"temperatura"`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ParamValue {
+    Bool(bool),
+    Int(i64),
+    Float(f32),
+    Color([f32; 3]),
+    Gradient(GradientName),
+}
+
+impl ParamValue {
+    pub fn as_f32(&self) -> Option<f32> {
+        match *self {
+            ParamValue::Int(v) => Some(v as f32),
+            ParamValue::Float(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_u32(&self) -> Option<u32> {
+        match *self {
+            ParamValue::Int(v) => u32::try_from(v).ok(),
+            ParamValue::Float(v) if v >= 0.0 && v.fract() == 0.0 => Some(v as u32),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            ParamValue::Bool(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_color(&self) -> Option<Vector3> {
+        match *self {
+            ParamValue::Color([r, g, b]) => Some(Vector3::new(r, g, b)),
+            _ => None,
+        }
+    }
+
+    pub fn as_gradient(&self) -> Option<GradientName> {
+        match *self {
+            ParamValue::Gradient(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            ParamValue::Bool(_) => "un booleano",
+            ParamValue::Int(_) => "un entero",
+            ParamValue::Float(_) => "un número",
+            ParamValue::Color(_) => "un color (r, g, b)",
+            ParamValue::Gradient(_) => "un nombre de gradiente",
+        }
+    }
+}
+
+/// Materials whose parameters can be read and written by name (scene files,
+/// presets, the parameter panel).
+pub trait Params {
+    /// Every parameter with its current value, in declaration order
+    fn params(&self) -> Vec<(&'static str, ParamValue)>;
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), String>;
+
+    fn param(&self, name: &str) -> Option<ParamValue> {
+        self.params().into_iter().find(|(n, _)| *n == name).map(|(_, v)| v)
+    }
+
+    /// Applies `(name, value)` pairs, stopping at the first bad one
+    fn set_params<'a>(&mut self, values: impl IntoIterator<Item = (&'a str, ParamValue)>) -> Result<(), String> {
+        for (name, value) in values {
+            self.set_param(name, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Error for a value of the wrong kind
+pub fn type_error(name: &str, expected: &str, value: &ParamValue) -> String {
+    format!("el parámetro '{name}' espera {expected}, no {}", value.kind())
+}
+
+/// Implements `Params` for a struct from a list of `field: kind` pairs, where kind is
+/// `f32`, `u32`, `bool`, `color` (a Vector3 field) or `gradient` (a GradientName field).
+macro_rules! impl_params {
+    ($ty:ty { $($field:ident : $kind:ident),* $(,)? }) => {
+        impl $crate::params::Params for $ty {
+            fn params(&self) -> Vec<(&'static str, $crate::params::ParamValue)> {
+                vec![$((stringify!($field), impl_params!(@get $kind, self.$field))),*]
+            }
+
+            fn set_param(&mut self, name: &str, value: $crate::params::ParamValue) -> Result<(), String> {
+                match name {
+                    $(stringify!($field) => { self.$field = impl_params!(@set $kind, name, value); Ok(()) })*
+                    _ => Err(format!("parámetro desconocido: '{name}'")),
+                }
+            }
+        }
+    };
+    (@get f32, $v:expr) => { $crate::params::ParamValue::Float($v) };
+    (@get u32, $v:expr) => { $crate::params::ParamValue::Int($v as i64) };
+    (@get bool, $v:expr) => { $crate::params::ParamValue::Bool($v) };
+    (@get color, $v:expr) => { $crate::params::ParamValue::Color([$v.x, $v.y, $v.z]) };
+    (@get gradient, $v:expr) => { $crate::params::ParamValue::Gradient($v) };
+    (@set f32, $name:expr, $value:expr) => {
+        $value.as_f32().ok_or_else(|| $crate::params::type_error($name, "un número", &$value))?
+    };
+    (@set u32, $name:expr, $value:expr) => {
+        $value.as_u32().ok_or_else(|| $crate::params::type_error($name, "un entero no negativo", &$value))?
+    };
+    (@set bool, $name:expr, $value:expr) => {
+        $value.as_bool().ok_or_else(|| $crate::params::type_error($name, "un booleano", &$value))?
+    };
+    (@set color, $name:expr, $value:expr) => {
+        $value.as_color().ok_or_else(|| $crate::params::type_error($name, "un color (r, g, b)", &$value))?
+    };
+    (@set gradient, $name:expr, $value:expr) => {
+        $value.as_gradient().ok_or_else(|| $crate::params::type_error($name, "un nombre de gradiente", &$value))?
+    };
+}
+pub(crate) use impl_params;
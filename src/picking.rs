@@ -0,0 +1,111 @@
+#![allow(dead_code)]
+
+use raylib::prelude::*;
+use crate::entity::Entity;
+use crate::scene::Scene;
+use crate::{ring_model, world_vertices, FragmentShader, VertexShader};
+
+/// What a ray hit: the entity, the distance along the ray and the world-space point
+#[derive(Clone, Copy, Debug)]
+pub struct Hit {
+    pub entity: usize,
+    pub distance: f32,
+    pub point: Vector3,
+}
+
+/// Distance along a unit-length ray to the first intersection with a sphere
+/// (0 when the origin is inside); None on a miss
+pub fn ray_sphere(origin: Vector3, direction: Vector3, center: Vector3, radius: f32) -> Option<f32> {
+    let oc = origin - center;
+    let b = oc.dot(direction);
+    let c = oc.dot(oc) - radius * radius;
+    if c <= 0.0 {
+        return Some(0.0);
+    }
+    let discriminant = b * b - c;
+    if b > 0.0 || discriminant < 0.0 {
+        return None;
+    }
+    Some(-b - discriminant.sqrt())
+}
+
+/// Möller-Trumbore: distance along the ray to triangle `abc`, hit from either side
+pub fn ray_triangle(origin: Vector3, direction: Vector3, a: Vector3, b: Vector3, c: Vector3) -> Option<f32> {
+    let (ab, ac) = (b - a, c - a);
+    let p = direction.cross(ac);
+    let det = ab.dot(p);
+    if det.abs() < 1e-9 {
+        return None; // ray parallel to the triangle
+    }
+    let inv_det = 1.0 / det;
+    let s = origin - a;
+    let u = s.dot(p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(ab);
+    let v = direction.dot(q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = ac.dot(q) * inv_det;
+    (t > 0.0).then_some(t)
+}
+
+/// Nearest triangle of a mesh hit by the ray, with the vertex shader applied as when drawn
+fn ray_mesh(origin: Vector3, direction: Vector3, model: &Matrix, vertices: &[Vector3], vshader: &VertexShader, time: f32) -> Option<f32> {
+    world_vertices(model, vertices, vshader, time)
+        .chunks_exact(3)
+        .filter_map(|tri| ray_triangle(origin, direction, tri[0].1, tri[1].1, tri[2].1))
+        .min_by(f32::total_cmp)
+}
+
+/// Bounding sphere of everything drawn for the entity, rings included. Flares push the
+/// surface out by up to their amplitude, so the sphere grows with it.
+fn bounds(entity: &Entity) -> (Vector3, f32) {
+    let scale = entity.world_scale();
+    let scale = scale.x.max(scale.y).max(scale.z);
+    let mut radius = entity.bounding_radius();
+    if let VertexShader::SolarFlare(material) = entity.vshader {
+        radius += material.flare_amplitude.abs() * scale;
+    }
+    if let Some(rings) = &entity.rings {
+        radius = radius.max(rings.vertices.iter().fold(0.0f32, |m, v| m.max(v.length())) * scale);
+    }
+    (entity.world_position(), radius)
+}
+
+/// Casts a world-space ray into the scene: bounding spheres first, then the triangles of
+/// the entities whose sphere was hit, nearest first. Glow shells are see-through and
+/// never picked, so a click on the corona selects the sun inside it.
+pub fn pick(scene: &Scene, origin: Vector3, direction: Vector3, time: f32) -> Option<Hit> {
+    let mut candidates: Vec<(f32, usize)> = scene.entities.iter().enumerate()
+        .filter(|(_, e)| !e.vertices.is_empty() && !matches!(e.fshader, FragmentShader::Glow { .. }))
+        .filter_map(|(i, e)| {
+            let (center, radius) = bounds(e);
+            ray_sphere(origin, direction, center, radius).map(|t| (t, i))
+        })
+        .collect();
+    candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut best: Option<Hit> = None;
+    for (sphere_distance, i) in candidates {
+        // Spheres are sorted by entry distance, so once one starts past the best hit
+        // nothing further along can be nearer
+        if best.is_some_and(|hit| sphere_distance > hit.distance) {
+            break;
+        }
+        let e = &scene.entities[i];
+        let body = ray_mesh(origin, direction, &e.world.matrix, &e.vertices, &e.vshader, time);
+        let rings = e.rings.as_ref()
+            .and_then(|rings| ray_mesh(origin, direction, &ring_model(e, rings), &rings.vertices, &VertexShader::Identity, time));
+        let nearest = match (body, rings) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        if let Some(distance) = nearest.filter(|&d| best.is_none_or(|hit| d < hit.distance)) {
+            best = Some(Hit { entity: i, distance, point: origin + direction * distance });
+        }
+    }
+    best
+}
@@ -0,0 +1,249 @@
+#![allow(dead_code)]
+
+use raylib::prelude::*;
+use crate::fragment::Fragment;
+use crate::light::{lambert, shade_blinn_phong};
+use crate::params::impl_params;
+use crate::procedural::{fbm3, ridged_fbm3};
+use crate::shadow::shadow_visibility;
+use crate::uniforms::Uniforms;
+use crate::{rotate_y, smoothstep};
+
+/// Parameters of the procedural planet shader. Heights live in [-1,1];
+/// everything below `sea_level` is ocean.
+#[derive(Clone, Copy, Debug)]
+pub struct PlanetMaterial {
+    // Terrain
+    pub seed: f32,
+    pub terrain_scale: f32,   // noise frequency on the unit sphere
+    pub octaves: u32,
+    pub ridged: f32,          // 0 = rolling FBM hills, 1 = fully ridged mountains
+    pub sea_level: f32,       // -1 disables oceans
+    pub band_count: f32,      // > 0 replaces terrain with gas-giant latitude bands
+    pub polar_cap: f32,       // |sin(latitude)| above which the surface is ice
+
+    // Palette
+    pub deep_color: Vector3,
+    pub shallow_color: Vector3,
+    pub low_color: Vector3,
+    pub mid_color: Vector3,
+    pub high_color: Vector3,
+    pub snow_color: Vector3,
+
+    // Ocean highlight
+    pub ocean_specular: f32,
+    pub ocean_shininess: f32,
+
+    // Clouds
+    pub cloud_coverage: f32,  // 0 = clear sky, 1 = overcast
+    pub cloud_scale: f32,
+    pub cloud_speed: f32,     // radians per second around the spin axis
+    pub cloud_color: Vector3,
+
+    // Atmosphere rim
+    pub atmosphere_color: Vector3,
+    pub atmosphere_strength: f32,
+    pub rim_power: f32,
+}
+
+impl PlanetMaterial {
+    /// Earth-like world with oceans, continents, snowy peaks and clouds
+    pub fn rocky() -> Self {
+        PlanetMaterial {
+            seed: 1.7,
+            terrain_scale: 1.6,
+            octaves: 6,
+            ridged: 0.45,
+            sea_level: 0.0,
+            band_count: 0.0,
+            polar_cap: 0.85,
+            deep_color: Vector3::new(0.01, 0.05, 0.20),
+            shallow_color: Vector3::new(0.05, 0.25, 0.45),
+            low_color: Vector3::new(0.76, 0.70, 0.50),
+            mid_color: Vector3::new(0.18, 0.42, 0.15),
+            high_color: Vector3::new(0.40, 0.33, 0.26),
+            snow_color: Vector3::new(0.95, 0.95, 0.97),
+            ocean_specular: 0.8,
+            ocean_shininess: 48.0,
+            cloud_coverage: 0.45,
+            cloud_scale: 2.5,
+            cloud_speed: 0.05,
+            cloud_color: Vector3::new(1.0, 1.0, 1.0),
+            atmosphere_color: Vector3::new(0.35, 0.6, 1.0),
+            atmosphere_strength: 0.8,
+            rim_power: 3.0,
+        }
+    }
+
+    /// Banded gas giant without a solid surface
+    pub fn gas_giant() -> Self {
+        PlanetMaterial {
+            seed: 4.2,
+            terrain_scale: 2.0,
+            octaves: 5,
+            ridged: 0.0,
+            sea_level: -1.0,
+            band_count: 9.0,
+            polar_cap: 1.1,
+            deep_color: Vector3::new(0.55, 0.35, 0.20),
+            shallow_color: Vector3::new(0.55, 0.35, 0.20),
+            low_color: Vector3::new(0.62, 0.42, 0.28),
+            mid_color: Vector3::new(0.85, 0.74, 0.58),
+            high_color: Vector3::new(0.95, 0.90, 0.80),
+            snow_color: Vector3::new(0.95, 0.90, 0.80),
+            ocean_specular: 0.0,
+            ocean_shininess: 1.0,
+            cloud_coverage: 0.0,
+            cloud_scale: 1.0,
+            cloud_speed: 0.0,
+            cloud_color: Vector3::new(1.0, 1.0, 1.0),
+            atmosphere_color: Vector3::new(0.9, 0.75, 0.55),
+            atmosphere_strength: 0.5,
+            rim_power: 2.0,
+        }
+    }
+
+    /// Frozen world: cracked ice plains, frozen seas and thin haze
+    pub fn ice() -> Self {
+        PlanetMaterial {
+            seed: 9.1,
+            terrain_scale: 2.4,
+            octaves: 5,
+            ridged: 0.8,
+            sea_level: -0.2,
+            band_count: 0.0,
+            polar_cap: 0.4,
+            deep_color: Vector3::new(0.45, 0.60, 0.75),
+            shallow_color: Vector3::new(0.65, 0.80, 0.90),
+            low_color: Vector3::new(0.80, 0.88, 0.95),
+            mid_color: Vector3::new(0.70, 0.78, 0.88),
+            high_color: Vector3::new(0.55, 0.62, 0.72),
+            snow_color: Vector3::new(0.97, 0.98, 1.0),
+            ocean_specular: 0.6,
+            ocean_shininess: 96.0,
+            cloud_coverage: 0.2,
+            cloud_scale: 3.0,
+            cloud_speed: 0.02,
+            cloud_color: Vector3::new(0.9, 0.95, 1.0),
+            atmosphere_color: Vector3::new(0.7, 0.85, 1.0),
+            atmosphere_strength: 0.4,
+            rim_power: 4.0,
+        }
+    }
+
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "rocky" => Some(Self::rocky()),
+            "gas_giant" => Some(Self::gas_giant()),
+            "ice" => Some(Self::ice()),
+            _ => None,
+        }
+    }
+}
+
+impl_params!(PlanetMaterial {
+    seed: f32,
+    terrain_scale: f32,
+    octaves: u32,
+    ridged: f32,
+    sea_level: f32,
+    band_count: f32,
+    polar_cap: f32,
+    deep_color: color,
+    shallow_color: color,
+    low_color: color,
+    mid_color: color,
+    high_color: color,
+    snow_color: color,
+    ocean_specular: f32,
+    ocean_shininess: f32,
+    cloud_coverage: f32,
+    cloud_scale: f32,
+    cloud_speed: f32,
+    cloud_color: color,
+    atmosphere_color: color,
+    atmosphere_strength: f32,
+    rim_power: f32,
+});
+
+/// Terrain height in [-1,1] for a unit direction
+fn height(dir: Vector3, m: &PlanetMaterial) -> f32 {
+    let p = dir * m.terrain_scale + Vector3::new(m.seed, m.seed * 0.37, m.seed * 0.71);
+    let smooth = fbm3(p, m.octaves, 2.0, 0.5);
+    let ridges = ridged_fbm3(p * 0.8, m.octaves, 2.1, 0.5) * 2.0 - 1.0;
+    smooth + (ridges - smooth) * m.ridged
+}
+
+/// Surface albedo plus specular strength/shininess at a unit direction
+fn surface(dir: Vector3, m: &PlanetMaterial) -> (Vector3, f32, f32) {
+    let latitude = dir.y.abs();
+
+    if m.band_count > 0.0 {
+        // Gas giant: turbulent latitude bands
+        let warp = fbm3(dir * m.terrain_scale + Vector3::new(m.seed, 0.0, 0.0), m.octaves, 2.0, 0.55);
+        let band = ((dir.y + warp * 0.15) * m.band_count * std::f32::consts::PI).sin() * 0.5 + 0.5;
+        let detail = fbm3(Vector3::new(dir.x * 6.0, dir.y * 40.0, dir.z * 6.0), 3, 2.0, 0.5) * 0.5 + 0.5;
+        let color = m.low_color.lerp(m.mid_color, band).lerp(m.high_color, smoothstep(0.6, 1.0, detail) * 0.5);
+        return (color, 0.05, 8.0);
+    }
+
+    let h = height(dir, m);
+    if h < m.sea_level {
+        // Ocean: darker with depth, glossy
+        let depth = smoothstep(m.sea_level, m.sea_level - 0.35, h);
+        let color = m.shallow_color.lerp(m.deep_color, depth);
+        let frozen = smoothstep(m.polar_cap - 0.05, m.polar_cap + 0.05, latitude);
+        return (color.lerp(m.snow_color, frozen), m.ocean_specular * (1.0 - frozen), m.ocean_shininess);
+    }
+
+    // Land: beach -> lowland -> highland -> snow by altitude, ice caps by latitude
+    let land = ((h - m.sea_level) / (1.0 - m.sea_level).max(1e-3)).clamp(0.0, 1.0);
+    let mut color = m.low_color.lerp(m.mid_color, smoothstep(0.02, 0.12, land));
+    color = color.lerp(m.high_color, smoothstep(0.35, 0.6, land));
+    color = color.lerp(m.snow_color, smoothstep(0.7, 0.85, land));
+    let cap = smoothstep(m.polar_cap - 0.05, m.polar_cap + 0.05, latitude + land * 0.1);
+    (color.lerp(m.snow_color, cap), 0.05 + cap * 0.2, 12.0)
+}
+
+/// Procedural planet: terrain/bands, specular ocean, animated clouds and atmosphere rim,
+/// lit by every scene light (including shadows).
+pub fn planet_shader(fragment: &Fragment, m: &PlanetMaterial, u: &Uniforms) -> Vector3 {
+    let dir = fragment.obj_position.normalized();
+    // Smooth sphere normal in world space instead of the flat face normal
+    let normal = u.normal_matrix.multiply_vector(dir).normalized();
+    let p = fragment.world_position;
+    let to_eye = (u.camera_position - p).normalized();
+
+    let (albedo, specular, shininess) = surface(dir, m);
+    let mut color = albedo * u.ambient + shade_blinn_phong(u.lights, u.shadows, p, normal, u.camera_position, albedo, specular, shininess);
+
+    // Clouds drift around the spin axis and only take diffuse light
+    if m.cloud_coverage > 0.0 {
+        let cdir = rotate_y(dir, u.time * m.cloud_speed);
+        let n = fbm3(cdir * m.cloud_scale + Vector3::new(0.0, m.seed * 3.1, 0.0), 5, 2.0, 0.5) * 0.5 + 0.5;
+        let density = smoothstep(1.0 - m.cloud_coverage, 1.0 - m.cloud_coverage + 0.25, n);
+        if density > 0.0 {
+            let mut lit = m.cloud_color * u.ambient;
+            for (i, light) in u.lights.iter().enumerate() {
+                let s = light.sample(p);
+                let vis = shadow_visibility(u.shadows, i, p, normal);
+                lit += m.cloud_color * s.radiance * (lambert(normal, s.direction) * vis);
+            }
+            color = color.lerp(lit, density);
+        }
+    }
+
+    // Atmosphere: glow at grazing angles, only on the lit side (with a little wrap)
+    if m.atmosphere_strength > 0.0 {
+        let rim = (1.0 - normal.dot(to_eye).max(0.0)).powf(m.rim_power);
+        let mut sunlit = 0.0;
+        for light in u.lights {
+            let s = light.sample(p);
+            let wrap = ((normal.dot(s.direction) + 0.3) / 1.3).clamp(0.0, 1.0);
+            sunlit += wrap * (s.radiance.x + s.radiance.y + s.radiance.z) / 3.0;
+        }
+        color += m.atmosphere_color * (rim * m.atmosphere_strength * sunlit.min(1.5));
+    }
+
+    color
+}
@@ -27,9 +27,10 @@ pub fn generate_uv_sphere(radius: f32, lat_segments: usize, lon_segments: usize)
             let p10 = sph(radius, phi1, theta0);
             let p11 = sph(radius, phi1, theta1);
 
-            // Two triangles per quad (p00, p10, p11) and (p00, p11, p01)
-            out.push(p00); out.push(p10); out.push(p11);
-            out.push(p00); out.push(p11); out.push(p01);
+            // Two triangles per quad (p00, p11, p10) and (p00, p01, p11),
+            // counter-clockwise seen from outside so face normals point outwards
+            out.push(p00); out.push(p11); out.push(p10);
+            out.push(p00); out.push(p01); out.push(p11);
         }
     }
     out
@@ -106,6 +107,27 @@ fn smoothstep(t: f32) -> f32 { t * t * (3.0 - 2.0 * t) }
 #[inline]
 fn lerp(a: f32, b: f32, t: f32) -> f32 { a + (b - a) * t }
 
+/// 1D value noise in [0,1]
+pub fn value_noise1(x: f32) -> f32 {
+    let i = x.floor() as i32;
+    let t = smoothstep(x - i as f32);
+    lerp(lattice_rand(i, 0, 0), lattice_rand(i + 1, 0, 0), t)
+}
+
+/// 1D FBM in [0,1], used for radial profiles such as ring bands
+pub fn fbm1(mut x: f32, octaves: u32, lacunarity: f32, gain: f32) -> f32 {
+    let mut amp = 0.5;
+    let mut sum = 0.0;
+    let mut total_amp = 0.0;
+    for _ in 0..octaves {
+        sum += value_noise1(x) * amp;
+        total_amp += amp;
+        x *= lacunarity;
+        amp *= gain;
+    }
+    if total_amp > 0.0 { sum / total_amp } else { 0.0 }
+}
+
 /// 3D value noise in [0,1]
 pub fn value_noise3(p: Vector3) -> f32 {
     let x0 = p.x.floor() as i32;
@@ -153,3 +175,21 @@ pub fn fbm3(mut p: Vector3, octaves: u32, lacunarity: f32, gain: f32) -> f32 {
     }
     if total_amp > 0.0 { sum / total_amp } else { 0.0 }
 }
+
+/// Ridged multifractal: sharp crests where the noise crosses zero; returns ~[0,1]
+pub fn ridged_fbm3(mut p: Vector3, octaves: u32, lacunarity: f32, gain: f32) -> f32 {
+    let mut amp = 0.5;
+    let mut sum = 0.0;
+    let mut total_amp = 0.0;
+    let mut weight = 1.0;
+    for _ in 0..octaves {
+        let n = 1.0 - (value_noise3(p) * 2.0 - 1.0).abs();
+        let n = n * n * weight;
+        weight = n.clamp(0.0, 1.0);
+        sum += n * amp;
+        total_amp += amp;
+        p.x *= lacunarity; p.y *= lacunarity; p.z *= lacunarity;
+        amp *= gain;
+    }
+    if total_amp > 0.0 { sum / total_amp } else { 0.0 }
+}
@@ -0,0 +1,142 @@
+#![allow(dead_code)]
+
+use raylib::prelude::*;
+use crate::fragment::Fragment;
+use crate::framebuffer::{BlendMode, RenderState};
+use crate::params::impl_params;
+use crate::procedural::{fbm1, generate_ring};
+use crate::smoothstep;
+use crate::uniforms::Uniforms;
+
+/// Parameters of the planetary ring shader. Radii are in ring object units,
+/// i.e. relative to the parent planet's mesh.
+#[derive(Clone, Copy, Debug)]
+pub struct RingMaterial {
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    pub planet_radius: f32,    // parent planet radius, for the shadow it casts on the ring
+    pub seed: f32,
+    pub band_frequency: f32,   // bands across the ring width
+    pub octaves: u32,
+    pub gap_threshold: f32,    // band density below this becomes a gap
+    pub inner_color: Vector3,
+    pub outer_color: Vector3,
+    pub opacity: f32,
+    pub forward_scatter: f32,  // extra brightness when looking towards the light through the ring
+    pub scatter_power: f32,
+    pub state: RenderState,
+}
+
+impl RingMaterial {
+    /// Saturn-like icy rings with a few dark gaps
+    pub fn icy(inner_radius: f32, outer_radius: f32, planet_radius: f32) -> Self {
+        RingMaterial {
+            inner_radius,
+            outer_radius,
+            planet_radius,
+            seed: 3.3,
+            band_frequency: 28.0,
+            octaves: 4,
+            gap_threshold: 0.3,
+            inner_color: Vector3::new(0.55, 0.48, 0.40),
+            outer_color: Vector3::new(0.85, 0.80, 0.70),
+            opacity: 0.85,
+            forward_scatter: 1.5,
+            scatter_power: 8.0,
+            state: RenderState::transparent(BlendMode::Alpha),
+        }
+    }
+}
+
+impl_params!(RingMaterial {
+    inner_radius: f32,
+    outer_radius: f32,
+    planet_radius: f32,
+    seed: f32,
+    band_frequency: f32,
+    octaves: u32,
+    gap_threshold: f32,
+    inner_color: color,
+    outer_color: color,
+    opacity: f32,
+    forward_scatter: f32,
+    scatter_power: f32,
+});
+
+/// Ring geometry attached to a planet entity. The ring follows the planet's
+/// translation and scale; `tilt` (Euler, radians) orients its plane.
+pub struct Rings {
+    pub vertices: Vec<Vector3>,
+    pub tilt: Vector3,
+    pub material: RingMaterial,
+}
+
+impl Rings {
+    pub fn new(material: RingMaterial, tilt: Vector3) -> Self {
+        Rings {
+            vertices: generate_ring(material.inner_radius, material.outer_radius, 96),
+            tilt,
+            material,
+        }
+    }
+
+    /// Swaps in `material`, regenerating the mesh when its radii differ
+    pub fn set_material(&mut self, material: RingMaterial) {
+        let resized = (material.inner_radius, material.outer_radius) != (self.material.inner_radius, self.material.outer_radius);
+        self.material = material;
+        if resized {
+            self.vertices = generate_ring(material.inner_radius, material.outer_radius, 96);
+        }
+    }
+}
+
+/// Soft shadow of a sphere on the segment from `p` towards the light
+fn sphere_shadow(p: Vector3, to_light: Vector3, light_distance: f32, center: Vector3, radius: f32) -> f32 {
+    let oc = center - p;
+    let t = oc.dot(to_light);
+    if t <= 0.0 || t >= light_distance {
+        return 1.0;
+    }
+    let closest = (oc - to_light * t).length();
+    smoothstep(radius * 0.97, radius * 1.03, closest)
+}
+
+/// Banded, double-sided, semi-transparent ring. Returns rgb + alpha.
+pub fn ring_shader(fragment: &Fragment, m: &RingMaterial, u: &Uniforms) -> Vector4 {
+    let obj = fragment.obj_position;
+    let r = (obj.x * obj.x + obj.z * obj.z).sqrt();
+    let t = ((r - m.inner_radius) / (m.outer_radius - m.inner_radius).max(1e-4)).clamp(0.0, 1.0);
+
+    // Radial profile from 1D noise: dense bands, faint ringlets and gaps
+    let band = fbm1(t * m.band_frequency + m.seed, m.octaves, 2.0, 0.5);
+    let density = smoothstep(m.gap_threshold, m.gap_threshold + 0.2, band);
+    let edge = smoothstep(0.0, 0.04, t) * smoothstep(1.0, 0.96, t);
+    let alpha = (m.opacity * density * edge).clamp(0.0, 1.0);
+    if alpha <= 0.0 {
+        return Vector4::new(0.0, 0.0, 0.0, 0.0);
+    }
+    let albedo = m.inner_color.lerp(m.outer_color, t) * (0.7 + 0.6 * band);
+
+    // Double-sided: face the normal towards the viewer
+    let p = fragment.world_position;
+    let to_eye = (u.camera_position - p).normalized();
+    let normal = if fragment.normal.dot(to_eye) < 0.0 { -fragment.normal } else { fragment.normal };
+
+    // Parent planet sits at the model origin; its radius scales with the model
+    let center = Vector3::new(u.model.m12, u.model.m13, u.model.m14);
+    let scale = Vector3::new(u.model.m0, u.model.m1, u.model.m2).length();
+    let planet_radius = m.planet_radius * scale;
+
+    let mut color = albedo * u.ambient;
+    for light in u.lights {
+        let s = light.sample(p);
+        let shadow = sphere_shadow(p, s.direction, s.distance, center, planet_radius);
+        // Thin particles scatter light to both sides of the plane
+        let diffuse = normal.dot(s.direction).abs() * 0.6 + 0.2;
+        // Forward scattering: bright when the light is behind the ring
+        let phase = (-to_eye).dot(s.direction).max(0.0).powf(m.scatter_power) * m.forward_scatter;
+        color += albedo * s.radiance * ((diffuse + phase) * shadow);
+    }
+
+    Vector4::new(color.x, color.y, color.z, alpha)
+}
@@ -0,0 +1,194 @@
+#![allow(dead_code)]
+
+use raylib::prelude::*;
+use std::path::PathBuf;
+use crate::camera::Projection;
+use crate::entity::Entity;
+use crate::ephemeris::DisplayScale;
+use crate::light::Light;
+use crate::matrix::{multiply_matrix_matrix, new_matrix4};
+use crate::orbit::tangent_yaw;
+
+/// Initial camera placement; `follow` names an entity whose position the camera targets.
+/// With a `parent`, eye and target are offsets from that entity and the camera is carried
+/// along as it moves.
+#[derive(Clone, Debug)]
+pub struct CameraStart {
+    pub eye: Vector3,
+    pub target: Vector3,
+    pub follow: Option<String>,
+    pub parent: Option<String>,
+    pub projection: Projection,
+    pub near: f32,
+    pub far: f32,
+}
+
+pub struct Scene {
+    pub entities: Vec<Entity>,
+    pub lights: Vec<Light>, // free-standing lights (fill, spot, ...) in world space
+    pub background: Color,
+    pub ambient: Vector3,
+    pub camera: CameraStart,
+    pub sources: Vec<PathBuf>, // files the scene was loaded from (scene, materials, meshes), watched for hot reload
+}
+
+impl Scene {
+    pub fn new(entities: Vec<Entity>, lights: Vec<Light>) -> Self {
+        Scene {
+            entities,
+            lights,
+            background: Color::new(4, 12, 36, 255),
+            ambient: Vector3::new(0.03, 0.03, 0.05),
+            camera: CameraStart {
+                eye: Vector3::new(0.0, 8.0, 30.0),
+                target: Vector3::zero(),
+                follow: None,
+                parent: None,
+                projection: Projection::default(),
+                near: 0.5,
+                far: 100.0,
+            },
+            sources: Vec::new(),
+        }
+    }
+
+    pub fn find(&self, name: &str) -> Option<&Entity> {
+        self.entities.iter().find(|ent| ent.name == name)
+    }
+
+    /// Moves orbiting entities along their Keplerian paths (relative to their parent,
+    /// so moons follow their planet) and applies axial spin.
+    pub fn update_motion(&mut self, time: f32) {
+        for e in &mut self.entities {
+            if let (false, Some(orbit)) = (e.simulated, &e.orbit) {
+                e.transform.translation = orbit.elements.position(time);
+            }
+            if e.spin != 0.0 || e.face_tangent {
+                let mut yaw = e.spin * time;
+                if let (true, Some(orbit)) = (e.face_tangent, &e.orbit) {
+                    yaw += tangent_yaw(orbit.elements.velocity(time));
+                }
+                e.spin_angle = yaw;
+            }
+        }
+    }
+
+    /// Places every ephemeris body at its position on Julian day `jd`
+    pub fn update_ephemeris(&mut self, jd: f64, scale: &DisplayScale) {
+        for e in &mut self.entities {
+            if let (false, Some(body)) = (e.simulated, e.ephemeris) {
+                e.transform.translation = scale.position(body, jd);
+            }
+        }
+    }
+
+    /// Scene-graph parent of every entity, by index. Free entities (simulated or
+    /// ephemeris-driven) and dangling names have none.
+    pub fn parent_indices(&self) -> Vec<Option<usize>> {
+        self.entities.iter()
+            .map(|e| match &e.parent {
+                Some(name) if !e.is_free() => self.entities.iter().position(|p| p.name == *name),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Entity indices ordered so every parent comes before its children
+    fn graph_order(parents: &[Option<usize>]) -> Vec<usize> {
+        let depth = |mut i: usize| {
+            let mut d = 0;
+            // the bound guards against cycles in the parent chain
+            while let (Some(p), true) = (parents[i], d < parents.len()) {
+                i = p;
+                d += 1;
+            }
+            d
+        };
+        let mut order: Vec<usize> = (0..parents.len()).collect();
+        order.sort_by_key(|&i| depth(i));
+        order
+    }
+
+    /// Recomputes the cached world matrices, parents first. A node is only rebuilt when
+    /// its local transform changed, it was marked dirty, or its parent was rebuilt.
+    /// Orbiting children inherit only the parent's position (their orbit is in world
+    /// axes); any other child inherits the parent's full transform.
+    pub fn update_transforms(&mut self) {
+        let parents = self.parent_indices();
+        let mut rebuilt = vec![false; self.entities.len()];
+        for i in Self::graph_order(&parents) {
+            let parent = parents[i];
+            let e = &self.entities[i];
+            let local = (e.transform, e.spin_angle);
+            let parent_rebuilt = parent.is_some_and(|p| rebuilt[p]);
+            if !e.world.dirty && e.world.local == Some(local) && e.world.parent == parent && !parent_rebuilt {
+                continue;
+            }
+
+            let model = e.local_transform().matrix();
+            let matrix = match parent {
+                Some(p) if e.orbit.is_some() => {
+                    let origin = self.entities[p].world_position();
+                    let to_parent = new_matrix4(
+                        1.0, 0.0, 0.0, origin.x,
+                        0.0, 1.0, 0.0, origin.y,
+                        0.0, 0.0, 1.0, origin.z,
+                        0.0, 0.0, 0.0, 1.0,
+                    );
+                    multiply_matrix_matrix(&to_parent, &model)
+                }
+                Some(p) => multiply_matrix_matrix(&self.entities[p].world.matrix, &model),
+                None => model,
+            };
+
+            let world = &mut self.entities[i].world;
+            world.matrix = matrix;
+            world.local = Some(local);
+            world.parent = parent;
+            world.dirty = false;
+            rebuilt[i] = true;
+        }
+    }
+
+    /// World velocity of an entity's Keplerian motion at `time` (parent velocities included)
+    pub fn orbit_velocity(&self, index: usize, time: f32) -> Vector3 {
+        let parents = self.parent_indices();
+        let mut velocity = Vector3::zero();
+        let mut current = Some(index);
+        let mut depth = 0;
+        while let (Some(i), true) = (current, depth < parents.len()) {
+            if let Some(orbit) = &self.entities[i].orbit {
+                velocity += orbit.elements.velocity(time);
+            }
+            current = parents[i];
+            depth += 1;
+        }
+        velocity
+    }
+
+    /// Bounding sphere (center, radius) around every shadow-casting entity
+    pub fn caster_bounds(&self) -> (Vector3, f32) {
+        let casters: Vec<&Entity> = self.entities.iter().filter(|e| e.casts_shadow).collect();
+        if casters.is_empty() {
+            return (Vector3::zero(), 1.0);
+        }
+        let center = casters.iter().fold(Vector3::zero(), |acc, e| acc + e.world_position()) / casters.len() as f32;
+        let radius = casters.iter()
+            .map(|e| (e.world_position() - center).length() + e.bounding_radius())
+            .fold(0.0f32, f32::max);
+        (center, radius)
+    }
+
+    /// All lights for the current frame: free-standing ones plus every entity
+    /// emitter, moved to the entity's current world position.
+    pub fn gather_lights(&self) -> Vec<Light> {
+        let mut lights = self.lights.clone();
+        for e in &self.entities {
+            if let Some(mut light) = e.light {
+                light.position = e.world_position();
+                lights.push(light);
+            }
+        }
+        lights
+    }
+}
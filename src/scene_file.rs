@@ -0,0 +1,714 @@
+#![allow(dead_code)]
+
+use raylib::prelude::*;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::{FragmentShader, VertexShader};
+use crate::entity::{Entity, WorldTransform};
+use crate::ephemeris;
+use crate::framebuffer::{BlendMode, RenderState};
+use crate::gradient::GradientName;
+use crate::light::Light;
+use crate::mesh::load_obj;
+use crate::orbit::{kepler_period, Orbit, OrbitalElements};
+use crate::params::{ParamValue, Params};
+use crate::planet::PlanetMaterial;
+use crate::procedural::generate_uv_sphere;
+use crate::ring::{RingMaterial, Rings};
+use crate::camera::Projection;
+use crate::scene::{CameraStart, Scene};
+use crate::sun::SunMaterial;
+use crate::transform::{Quat, Transform};
+
+type Vec3 = [f32; 3];
+type ParamMap = BTreeMap<String, ParamValue>;
+
+fn v3(v: Vec3) -> Vector3 {
+    Vector3::new(v[0], v[1], v[2])
+}
+
+fn one() -> f32 { 1.0 }
+fn yes() -> bool { true }
+fn white() -> Vec3 { [1.0, 1.0, 1.0] }
+
+// --- File format (RON). Unknown fields are errors so typos are reported. ---
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneDesc {
+    #[serde(default)]
+    background: Option<(u8, u8, u8)>,
+    #[serde(default)]
+    ambient: Option<Vec3>,
+    #[serde(default)]
+    camera: Option<CameraDesc>,
+    #[serde(default)]
+    lights: Vec<LightDesc>,
+    entities: Vec<EntityDesc>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CameraDesc {
+    eye: Vec3,
+    #[serde(default)]
+    target: Vec3,
+    #[serde(default)]
+    follow: Option<String>,
+    #[serde(default)]
+    parent: Option<String>, // eye and target become offsets from this entity
+    #[serde(default)]
+    projection: Option<ProjectionDesc>,
+    #[serde(default)]
+    near: Option<f32>,
+    #[serde(default)]
+    far: Option<f32>,
+}
+
+/// Off-axis projections depend on the display wall, so they are chosen on the command
+/// line (`--tile`) rather than in the scene
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+enum ProjectionDesc {
+    Perspective { fov_y: f32 }, // radians
+    Orthographic { height: f32 },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+enum LightDesc {
+    Point {
+        #[serde(default)]
+        position: Vec3,
+        #[serde(default = "white")]
+        color: Vec3,
+        #[serde(default = "one")]
+        intensity: f32,
+        #[serde(default)]
+        attenuation: Option<Vec3>,
+        #[serde(default)]
+        shadows: bool,
+    },
+    Directional {
+        direction: Vec3,
+        #[serde(default = "white")]
+        color: Vec3,
+        #[serde(default = "one")]
+        intensity: f32,
+        #[serde(default)]
+        shadows: bool,
+    },
+    Spot {
+        position: Vec3,
+        direction: Vec3,
+        inner_angle: f32,
+        outer_angle: f32,
+        #[serde(default = "white")]
+        color: Vec3,
+        #[serde(default = "one")]
+        intensity: f32,
+        #[serde(default)]
+        attenuation: Option<Vec3>,
+        #[serde(default)]
+        shadows: bool,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EntityDesc {
+    name: String,
+    #[serde(default)]
+    parent: Option<String>,
+    mesh: MeshDesc,
+    #[serde(default)]
+    translation: Vec3,
+    #[serde(default)]
+    rotation: Vec3, // Euler angles, applied X then Y then Z
+    #[serde(default)]
+    scale: ScaleDesc,
+    #[serde(default)]
+    vertex_shader: VertexShaderDesc,
+    material: MaterialDesc,
+    #[serde(default)]
+    light: Option<LightDesc>,
+    #[serde(default = "yes")]
+    casts_shadow: bool,
+    #[serde(default)]
+    rings: Option<RingsDesc>,
+    #[serde(default)]
+    orbit: Option<OrbitDesc>,
+    #[serde(default)]
+    spin: f32,
+    #[serde(default)]
+    face_tangent: bool,
+    #[serde(default)]
+    mass: f32,
+    #[serde(default)]
+    ephemeris: Option<String>,
+}
+
+/// `2.0` or per axis `(1.0, 0.9, 1.0)` (e.g. an oblate planet)
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ScaleDesc {
+    Uniform(f32),
+    Axes(Vec3),
+}
+
+impl Default for ScaleDesc {
+    fn default() -> Self {
+        ScaleDesc::Uniform(1.0)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+enum MeshDesc {
+    Sphere {
+        #[serde(default = "one")]
+        radius: f32,
+        #[serde(default = "default_rings")]
+        rings: usize,
+        #[serde(default = "default_segments")]
+        segments: usize,
+    },
+    Obj(String), // path relative to the scene file
+}
+
+fn default_rings() -> usize { 20 }
+fn default_segments() -> usize { 28 }
+
+#[derive(Deserialize, Default)]
+enum VertexShaderDesc {
+    #[default]
+    Identity,
+    SolarFlare,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+enum MaterialDesc {
+    Solar {
+        #[serde(default)]
+        params: ParamMap, // also drives the SolarFlare vertex shader of the same entity
+    },
+    Lit {
+        albedo: Vec3,
+        #[serde(default)]
+        specular: f32,
+        #[serde(default = "default_shininess")]
+        shininess: f32,
+    },
+    Planet {
+        #[serde(default = "default_preset")]
+        preset: String,
+        #[serde(default)]
+        params: ParamMap,
+    },
+    Glow {
+        color: Vec3,
+        #[serde(default = "default_power")]
+        power: f32,
+        #[serde(default)]
+        ramp: GradientName,
+        #[serde(default)]
+        blend: BlendDesc,
+    },
+    File(String), // another RON file holding one material, relative to the scene file
+}
+
+fn default_shininess() -> f32 { 8.0 }
+fn default_preset() -> String { "rocky".to_string() }
+fn default_power() -> f32 { 3.0 }
+
+#[derive(Deserialize, Default)]
+enum BlendDesc {
+    Alpha,
+    #[default]
+    Additive,
+    Premultiplied,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RingsDesc {
+    inner_radius: f32,
+    outer_radius: f32,
+    #[serde(default = "one")]
+    planet_radius: f32,
+    #[serde(default)]
+    tilt: Vec3,
+    #[serde(default)]
+    params: ParamMap,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OrbitDesc {
+    semi_major_axis: f32,
+    #[serde(default)]
+    eccentricity: f32,
+    #[serde(default)]
+    inclination: f32,
+    #[serde(default)]
+    ascending_node: f32,
+    #[serde(default)]
+    argument_of_periapsis: f32,
+    #[serde(default)]
+    mean_anomaly_at_epoch: f32,
+    #[serde(default)]
+    period: Option<f32>, // derived from the masses (Kepler's third law) when omitted
+}
+
+// --- Errors ---
+
+/// A scene file problem, located at a line of the file when possible
+#[derive(Debug, Clone)]
+pub struct SceneError {
+    pub path: String,
+    pub line: Option<usize>, // 1-based
+    pub column: Option<usize>,
+    pub message: String,
+    pub source_line: Option<String>,
+}
+
+impl std::fmt::Display for SceneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(col)) => write!(f, "{}:{}:{}: {}", self.path, line, col, self.message)?,
+            (Some(line), None) => write!(f, "{}:{}: {}", self.path, line, self.message)?,
+            _ => write!(f, "{}: {}", self.path, self.message)?,
+        }
+        if let (Some(line), Some(text)) = (self.line, &self.source_line) {
+            let gutter = line.to_string();
+            write!(f, "\n {gutter} | {text}")?;
+            if let Some(col) = self.column {
+                write!(f, "\n {} | {}^", " ".repeat(gutter.len()), " ".repeat(col.saturating_sub(1)))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Context<'a> {
+    path: &'a str,
+    source: &'a str,
+}
+
+impl Context<'_> {
+    fn syntax_error(&self, e: ron::error::SpannedError) -> SceneError {
+        self.error(Some(e.span.start.line), Some(e.span.start.col), e.code.to_string())
+    }
+
+    fn error(&self, line: Option<usize>, column: Option<usize>, message: String) -> SceneError {
+        SceneError {
+            path: self.path.to_string(),
+            line,
+            column,
+            message,
+            source_line: line.and_then(|l| self.source.lines().nth(l - 1)).map(|s| s.to_string()),
+        }
+    }
+
+    /// Error pointed at the first line mentioning `needle` (a name or a value),
+    /// searching from the line that declares entity `entity` when given
+    fn error_near(&self, entity: Option<&str>, needle: &str, message: String) -> SceneError {
+        let start = entity
+            .and_then(|name| self.find_line(&format!("\"{name}\""), 0))
+            .unwrap_or(0);
+        let line = self.find_line(needle, start).or(Some(start + 1).filter(|_| start > 0));
+        let column = line
+            .and_then(|l| self.source.lines().nth(l - 1))
+            .and_then(|text| text.find(needle))
+            .map(|c| c + 1);
+        self.error(line, column, message)
+    }
+
+    fn find_line(&self, needle: &str, from: usize) -> Option<usize> {
+        self.source.lines().enumerate().skip(from.saturating_sub(1))
+            .find(|(_, text)| text.contains(needle))
+            .map(|(i, _)| i + 1)
+    }
+}
+
+// --- Loading ---
+
+/// Folder of the built-in scenes, relative to the working directory
+pub const BUILT_IN_DIR: &str = "scenes";
+
+/// The built-in scenes and the files they use, compiled in so the program runs without
+/// the `scenes` folder; paths are relative to it
+const BUILT_IN: [(&str, &str); 3] = [
+    ("demo.ron", include_str!("../scenes/demo.ron")),
+    ("solar_system.ron", include_str!("../scenes/solar_system.ron")),
+    ("materials/sun.ron", include_str!("../scenes/materials/sun.ron")),
+];
+
+/// Builds a built-in scene (`demo.ron`, `solar_system.ron`) from its compiled-in copy
+pub fn built_in_scene(name: &str) -> Result<Scene, SceneError> {
+    let source = BUILT_IN.iter().find(|(file, _)| *file == name).map(|(_, source)| *source).ok_or_else(|| SceneError {
+        path: name.to_string(),
+        line: None,
+        column: None,
+        message: "no es una escena incorporada".to_string(),
+        source_line: None,
+    })?;
+    let mut scene = parse_scene(source, &format!("{name} (incorporada)"), Path::new(BUILT_IN_DIR))?;
+    // Nothing on disk to watch
+    scene.sources.clear();
+    Ok(scene)
+}
+
+/// Reads a file the scene refers to; a missing file of the built-in scenes falls back to
+/// its compiled-in copy
+fn read_source(path: &Path) -> std::io::Result<String> {
+    std::fs::read_to_string(path).or_else(|error| {
+        BUILT_IN.iter()
+            .find(|(file, _)| Path::new(BUILT_IN_DIR).join(file) == path)
+            .map(|(_, source)| source.to_string())
+            .ok_or(error)
+    })
+}
+
+/// Reads and builds a scene from a RON file
+pub fn load_scene(path: &Path) -> Result<Scene, SceneError> {
+    let name = path.display().to_string();
+    let source = std::fs::read_to_string(path).map_err(|e| SceneError {
+        path: name.clone(),
+        line: None,
+        column: None,
+        message: format!("no se puede leer el archivo: {e}"),
+        source_line: None,
+    })?;
+    let mut scene = parse_scene(&source, &name, path.parent().unwrap_or(Path::new(".")))?;
+    scene.sources.insert(0, path.to_path_buf());
+    Ok(scene)
+}
+
+/// Reads a standalone material file, such as a preset, on its own
+pub fn load_material(path: &Path) -> Result<FragmentShader, SceneError> {
+    let name = path.display().to_string();
+    let source = std::fs::read_to_string(path).map_err(|e| SceneError {
+        path: name.clone(),
+        line: None,
+        column: None,
+        message: format!("no se puede leer el archivo: {e}"),
+        source_line: None,
+    })?;
+    let cx = Context { path: &name, source: &source };
+    let desc: MaterialDesc = ron_options().from_str(&source).map_err(|e| cx.syntax_error(e))?;
+    if let MaterialDesc::File(_) = desc {
+        return Err(cx.error(Some(1), None, "un archivo de material no puede incluir otro archivo de material".to_string()));
+    }
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    build_material(&cx, &stem, &desc, path.parent().unwrap_or(Path::new(".")), &mut Vec::new())
+}
+
+// implicit_some: optional fields can be written without `Some(...)`
+fn ron_options() -> ron::Options {
+    ron::Options::default().with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+}
+
+/// Builds a scene from RON source; `base_dir` resolves relative OBJ and material paths.
+/// Every file read on the way is listed in `scene.sources`.
+pub fn parse_scene(source: &str, path: &str, base_dir: &Path) -> Result<Scene, SceneError> {
+    let cx = Context { path, source };
+    let desc: SceneDesc = ron_options().from_str(source).map_err(|e| cx.syntax_error(e))?;
+
+    let mut scene = Scene::new(Vec::new(), desc.lights.iter().map(build_light).collect());
+    if let Some((r, g, b)) = desc.background {
+        scene.background = Color::new(r, g, b, 255);
+    }
+    if let Some(ambient) = desc.ambient {
+        scene.ambient = v3(ambient);
+    }
+    if let Some(camera) = desc.camera {
+        let defaults = scene.camera.clone();
+        scene.camera = CameraStart {
+            eye: v3(camera.eye),
+            target: v3(camera.target),
+            follow: camera.follow,
+            parent: camera.parent,
+            projection: match camera.projection {
+                Some(ProjectionDesc::Perspective { fov_y }) => Projection::Perspective { fov_y },
+                Some(ProjectionDesc::Orthographic { height }) => Projection::Orthographic { height },
+                None => defaults.projection,
+            },
+            near: camera.near.unwrap_or(defaults.near),
+            far: camera.far.unwrap_or(defaults.far),
+        };
+    }
+
+    for e in &desc.entities {
+        if scene.find(&e.name).is_some() {
+            return Err(cx.error_near(None, &format!("\"{}\"", e.name), format!("nombre de entidad repetido: '{}'", e.name)));
+        }
+        let entity = build_entity(&cx, e, base_dir, &mut scene.sources)?;
+        scene.entities.push(entity);
+    }
+
+    // Second pass: parents reference other entities by name
+    for e in &desc.entities {
+        let Some(parent) = &e.parent else { continue };
+        if scene.find(parent).is_none() {
+            return Err(cx.error_near(Some(&e.name), &format!("\"{parent}\""), format!("el padre '{parent}' no es una entidad")));
+        }
+        // Walk up the chain; coming back to this entity means a cycle
+        let mut current = Some(parent.as_str());
+        for _ in 0..desc.entities.len() {
+            let Some(name) = current else { break };
+            if name == e.name {
+                return Err(cx.error_near(Some(&e.name), &format!("\"{parent}\""), format!("la cadena de padres de '{}' vuelve a sí misma", e.name)));
+            }
+            current = scene.find(name).and_then(|p| p.parent.as_deref());
+        }
+    }
+
+    for (i, e) in desc.entities.iter().enumerate() {
+        let Some(orbit) = &e.orbit else { continue };
+        let parent = e.parent.as_deref().and_then(|name| scene.find(name));
+        let period = match orbit.period {
+            Some(period) => period,
+            None => {
+                let gm = parent.map_or(0.0, |p| p.mass) + e.mass;
+                if gm <= 0.0 {
+                    return Err(cx.error_near(Some(&e.name), "orbit", format!(
+                        "la órbita de '{}' no tiene periodo ni masa de la que derivarlo", e.name
+                    )));
+                }
+                kepler_period(orbit.semi_major_axis, gm)
+            }
+        };
+        scene.entities[i].orbit = Some(Orbit {
+            elements: OrbitalElements {
+                semi_major_axis: orbit.semi_major_axis,
+                eccentricity: orbit.eccentricity,
+                inclination: orbit.inclination,
+                ascending_node: orbit.ascending_node,
+                argument_of_periapsis: orbit.argument_of_periapsis,
+                mean_anomaly_at_epoch: orbit.mean_anomaly_at_epoch,
+                period,
+            },
+        });
+    }
+
+    if let Some(follow) = &scene.camera.follow {
+        if scene.find(follow).is_none() {
+            return Err(cx.error_near(None, &format!("\"{follow}\""), format!("la cámara sigue a una entidad desconocida: '{follow}'")));
+        }
+    }
+    if let Some(parent) = &scene.camera.parent {
+        if scene.find(parent).is_none() {
+            return Err(cx.error_near(None, &format!("\"{parent}\""), format!("el padre de la cámara '{parent}' no es una entidad")));
+        }
+    }
+    let camera = &scene.camera;
+    let finite = |v: Vector3| v.x.is_finite() && v.y.is_finite() && v.z.is_finite();
+    if !finite(camera.eye) || !finite(camera.target) {
+        return Err(cx.error_near(None, "camera", "la cámara necesita eye y target finitos".to_string()));
+    }
+    // The view direction and the orbit distance both come from target - eye
+    if camera.eye == camera.target {
+        return Err(cx.error_near(None, "camera", format!("eye y target de la cámara coinciden ({}, {}, {})", camera.eye.x, camera.eye.y, camera.eye.z)));
+    }
+    if camera.near <= 0.0 || camera.far <= camera.near {
+        return Err(cx.error_near(None, "near", format!("la cámara necesita 0 < near < far (near {}, far {})", camera.near, camera.far)));
+    }
+    match camera.projection {
+        Projection::Perspective { fov_y } if !(fov_y > 0.0 && fov_y < std::f32::consts::PI) => {
+            return Err(cx.error_near(None, "fov_y", format!("fov_y debe estar entre 0 y π radianes, no {fov_y}")));
+        }
+        Projection::Orthographic { height } if !(height.is_finite() && height > 0.0) => {
+            return Err(cx.error_near(None, "height", format!("la altura ortográfica debe ser positiva, no {height}")));
+        }
+        _ => {}
+    }
+    Ok(scene)
+}
+
+fn build_light(desc: &LightDesc) -> Light {
+    let (light, attenuation, shadows) = match *desc {
+        LightDesc::Point { position, color, intensity, attenuation, shadows } => {
+            (Light::point(v3(position), v3(color), intensity), attenuation, shadows)
+        }
+        LightDesc::Directional { direction, color, intensity, shadows } => {
+            (Light::directional(v3(direction), v3(color), intensity), None, shadows)
+        }
+        LightDesc::Spot { position, direction, inner_angle, outer_angle, color, intensity, attenuation, shadows } => {
+            (Light::spot(v3(position), v3(direction), inner_angle, outer_angle, v3(color), intensity), attenuation, shadows)
+        }
+    };
+    let light = match attenuation {
+        Some([c, l, q]) => light.with_attenuation(c, l, q),
+        None => light,
+    };
+    if shadows { light.with_shadows() } else { light }
+}
+
+fn apply_params(cx: &Context, entity: &str, material: &mut impl Params, params: &ParamMap) -> Result<(), SceneError> {
+    for (name, value) in params {
+        material.set_param(name, *value).map_err(|message| cx.error_near(Some(entity), name, message))?;
+    }
+    Ok(())
+}
+
+/// Reads a material file; its errors are reported against that file
+fn load_material_file(cx: &Context, entity: &str, file: &str, base_dir: &Path, sources: &mut Vec<PathBuf>) -> Result<FragmentShader, SceneError> {
+    let path = base_dir.join(file);
+    sources.push(path.clone());
+    let source = read_source(&path)
+        .map_err(|e| cx.error_near(Some(entity), file, format!("no se puede leer el archivo de material: {e}")))?;
+    let name = path.display().to_string();
+    let mcx = Context { path: &name, source: &source };
+    let desc: MaterialDesc = ron_options().from_str(&source).map_err(|e| mcx.syntax_error(e))?;
+    if let MaterialDesc::File(_) = desc {
+        return Err(mcx.error(Some(1), None, "un archivo de material no puede incluir otro archivo de material".to_string()));
+    }
+    build_material(&mcx, entity, &desc, base_dir, sources)
+}
+
+fn build_material(cx: &Context, entity: &str, desc: &MaterialDesc, base_dir: &Path, sources: &mut Vec<PathBuf>) -> Result<FragmentShader, SceneError> {
+    Ok(match desc {
+        MaterialDesc::Solar { params } => {
+            let mut material = SunMaterial::default();
+            apply_params(cx, entity, &mut material, params)?;
+            // The turbulence time wraps modulo the period; 0 or NaN would make every pixel NaN
+            if !(material.loop_period.is_finite() && material.loop_period > 0.0) {
+                return Err(cx.error_near(Some(entity), "loop_period", format!("loop_period debe ser positivo, no {}", material.loop_period)));
+            }
+            FragmentShader::Solar(material)
+        }
+        MaterialDesc::Lit { albedo, specular, shininess } => {
+            FragmentShader::Lit { albedo: v3(*albedo), specular: *specular, shininess: *shininess }
+        }
+        MaterialDesc::Planet { preset, params } => {
+            let mut material = PlanetMaterial::preset(preset).ok_or_else(|| {
+                cx.error_near(Some(entity), &format!("\"{preset}\""), format!("preset de planeta desconocido: '{preset}' (rocky, gas_giant, ice)"))
+            })?;
+            apply_params(cx, entity, &mut material, params)?;
+            FragmentShader::Planet(material)
+        }
+        MaterialDesc::Glow { color, power, ramp, blend } => FragmentShader::Glow {
+            color: v3(*color),
+            power: *power,
+            ramp: *ramp,
+            state: RenderState::transparent(match blend {
+                BlendDesc::Alpha => BlendMode::Alpha,
+                BlendDesc::Additive => BlendMode::Additive,
+                BlendDesc::Premultiplied => BlendMode::Premultiplied,
+            }),
+        },
+        MaterialDesc::File(file) => load_material_file(cx, entity, file, base_dir, sources)?,
+    })
+}
+
+fn build_entity(cx: &Context, e: &EntityDesc, base_dir: &Path, sources: &mut Vec<PathBuf>) -> Result<Entity, SceneError> {
+    let vertices = match &e.mesh {
+        MeshDesc::Sphere { radius, rings, segments } => generate_uv_sphere(*radius, *rings, *segments),
+        MeshDesc::Obj(file) => {
+            let path = base_dir.join(file);
+            sources.push(path.clone());
+            load_obj(&path).map_err(|message| cx.error_near(Some(&e.name), file, message))?
+        }
+    };
+
+    let fshader = build_material(cx, &e.name, &e.material, base_dir, sources)?;
+
+    let rings = match &e.rings {
+        Some(r) => {
+            let mut material = RingMaterial::icy(r.inner_radius, r.outer_radius, r.planet_radius);
+            apply_params(cx, &e.name, &mut material, &r.params)?;
+            Some(Rings::new(material, v3(r.tilt)))
+        }
+        None => None,
+    };
+
+    let ephemeris = match &e.ephemeris {
+        Some(name) => Some(ephemeris::Body::ALL.into_iter().find(|b| b.name() == name).ok_or_else(|| {
+            cx.error_near(Some(&e.name), &format!("\"{name}\""), format!("cuerpo de efemérides desconocido: '{name}'"))
+        })?),
+        None => None,
+    };
+
+    Ok(Entity {
+        name: e.name.clone(),
+        parent: e.parent.clone(),
+        transform: Transform::new(
+            v3(e.translation),
+            Quat::from_euler(v3(e.rotation)),
+            match e.scale {
+                ScaleDesc::Uniform(s) => Vector3::new(s, s, s),
+                ScaleDesc::Axes(axes) => v3(axes),
+            },
+        ),
+        spin_angle: 0.0,
+        world: WorldTransform::default(),
+        vertices,
+        vshader: match e.vertex_shader {
+            VertexShaderDesc::Identity => VertexShader::Identity,
+            VertexShaderDesc::SolarFlare => VertexShader::SolarFlare(match fshader {
+                FragmentShader::Solar(material) => material,
+                _ => SunMaterial::default(),
+            }),
+        },
+        fshader,
+        light: e.light.as_ref().map(build_light),
+        casts_shadow: e.casts_shadow,
+        rings,
+        orbit: None, // resolved once every entity exists
+        spin: e.spin,
+        face_tangent: e.face_tangent,
+        mass: e.mass,
+        simulated: false,
+        ephemeris,
+    })
+}
+
+// --- Saving (presets from the parameter panel) ---
+
+fn value_source(value: &ParamValue) -> String {
+    match value {
+        ParamValue::Bool(v) => v.to_string(),
+        ParamValue::Int(v) => v.to_string(),
+        ParamValue::Float(v) => format!("{v:?}"),
+        ParamValue::Color([r, g, b]) => format!("({r:?}, {g:?}, {b:?})"),
+        ParamValue::Gradient(name) => format!("\"{name}\""),
+    }
+}
+
+/// `{ "name": value, ... }` with one parameter per line, in declaration order
+fn params_source(params: &[(&str, ParamValue)]) -> String {
+    let lines: String = params.iter().map(|(name, value)| format!("    \"{name}\": {},\n", value_source(value))).collect();
+    format!("{{\n{lines}}}")
+}
+
+/// Source of a material file for `shader`, loadable with `material: File("...")`.
+/// Planets list every parameter, so the preset they started from does not matter.
+/// Ring materials belong to a `rings` block instead (see `ring_params_source`): None.
+pub fn material_source(shader: &FragmentShader) -> Option<String> {
+    let c = |v: Vector3| format!("({:?}, {:?}, {:?})", v.x, v.y, v.z);
+    Some(match shader {
+        FragmentShader::Solar(material) => format!("Solar(params: {})", params_source(&material.params())),
+        FragmentShader::Planet(material) => format!("Planet(params: {})", params_source(&material.params())),
+        FragmentShader::Lit { albedo, specular, shininess } => {
+            format!("Lit(albedo: {}, specular: {specular:?}, shininess: {shininess:?})", c(*albedo))
+        }
+        FragmentShader::Glow { color, power, ramp, state } => {
+            let blend = match state.blend {
+                BlendMode::Alpha => "Alpha",
+                BlendMode::Premultiplied => "Premultiplied",
+                BlendMode::Additive | BlendMode::Opaque => "Additive",
+            };
+            format!("Glow(color: {}, power: {power:?}, ramp: \"{ramp}\", blend: {blend})", c(*color))
+        }
+        FragmentShader::Ring(_) => return None,
+    })
+}
+
+/// The `params` map of a `rings` block holding every parameter of `material`
+pub fn ring_params_source(material: &RingMaterial) -> String {
+    params_source(&material.params())
+}
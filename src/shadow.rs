@@ -0,0 +1,206 @@
+#![allow(dead_code)]
+
+use raylib::prelude::*;
+use std::f32::consts::PI;
+use crate::light::{Light, LightKind};
+use crate::matrix::*;
+
+pub struct ShadowSettings {
+    pub enabled: bool,
+    pub resolution: u32,  // texels per side of every shadow face
+    pub depth_bias: f32,  // world units subtracted before the depth comparison
+    pub normal_bias: f32, // world units the receiver is pushed along its normal
+    pub pcf_radius: i32,  // 0 = single tap, 1 = 3x3, 2 = 5x5 ...
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        ShadowSettings {
+            enabled: true,
+            resolution: 256,
+            depth_bias: 0.05,
+            normal_bias: 0.08,
+            pcf_radius: 1,
+        }
+    }
+}
+
+/// One depth target rendered from the light: a single face for directional and
+/// spot lights, one of six cube faces for point lights.
+pub struct ShadowFace {
+    pub view: Matrix,
+    pub projection: Matrix,
+    pub depth: Vec<f32>, // light-space depth per texel, INFINITY where nothing was drawn
+}
+
+/// How depth is measured from the light.
+enum DepthMetric {
+    // Distance to the light position (point and spot lights)
+    Radial(Vector3),
+    // Distance along the light direction from a plane (directional lights)
+    Planar { origin: Vector3, direction: Vector3 },
+}
+
+pub struct ShadowMap {
+    pub light_index: usize,
+    pub resolution: u32,
+    pub viewport: Matrix,
+    pub faces: Vec<ShadowFace>,
+    metric: DepthMetric,
+    depth_bias: f32,
+    normal_bias: f32,
+    pcf_radius: i32,
+}
+
+// Cube face order: +X, -X, +Y, -Y, +Z, -Z
+const CUBE_FACES: [(Vector3, Vector3); 6] = [
+    (Vector3 { x: 1.0, y: 0.0, z: 0.0 },  Vector3 { x: 0.0, y: 1.0, z: 0.0 }),
+    (Vector3 { x: -1.0, y: 0.0, z: 0.0 }, Vector3 { x: 0.0, y: 1.0, z: 0.0 }),
+    (Vector3 { x: 0.0, y: 1.0, z: 0.0 },  Vector3 { x: 0.0, y: 0.0, z: 1.0 }),
+    (Vector3 { x: 0.0, y: -1.0, z: 0.0 }, Vector3 { x: 0.0, y: 0.0, z: -1.0 }),
+    (Vector3 { x: 0.0, y: 0.0, z: 1.0 },  Vector3 { x: 0.0, y: 1.0, z: 0.0 }),
+    (Vector3 { x: 0.0, y: 0.0, z: -1.0 }, Vector3 { x: 0.0, y: 1.0, z: 0.0 }),
+];
+
+impl ShadowMap {
+    /// Sets up the light's view/projection for every face. `scene_center` and
+    /// `scene_radius` bound the casters, used to fit directional maps and far planes.
+    pub fn new(light_index: usize, light: &Light, settings: &ShadowSettings, scene_center: Vector3, scene_radius: f32) -> Self {
+        let res = settings.resolution.max(1);
+        let texels = (res * res) as usize;
+        let radius = scene_radius.max(1.0);
+
+        let (faces, metric) = match light.kind {
+            LightKind::Point => {
+                let far = (light.position - scene_center).length() + radius;
+                let projection = create_projection_matrix(PI / 2.0, 1.0, 0.05, far.max(1.0));
+                let faces = CUBE_FACES.iter().map(|(dir, up)| ShadowFace {
+                    view: create_look_to_matrix(light.position, *dir, *up),
+                    projection,
+                    depth: vec![f32::INFINITY; texels],
+                }).collect();
+                (faces, DepthMetric::Radial(light.position))
+            }
+            LightKind::Spot { outer_angle, .. } => {
+                let far = (light.position - scene_center).length() + radius;
+                let fov = (outer_angle * 2.0).clamp(0.1, PI * 0.95);
+                let face = ShadowFace {
+                    view: create_look_to_matrix(light.position, light.direction, pick_up(light.direction)),
+                    projection: create_projection_matrix(fov, 1.0, 0.05, far.max(1.0)),
+                    depth: vec![f32::INFINITY; texels],
+                };
+                (vec![face], DepthMetric::Radial(light.position))
+            }
+            LightKind::Directional => {
+                // Fit an orthographic box around the scene bounds
+                let origin = scene_center - light.direction * (radius * 2.0);
+                let face = ShadowFace {
+                    view: create_view_matrix(origin, scene_center, pick_up(light.direction)),
+                    projection: create_orthographic_matrix(-radius, radius, -radius, radius, 0.0, radius * 4.0),
+                    depth: vec![f32::INFINITY; texels],
+                };
+                (vec![face], DepthMetric::Planar { origin, direction: light.direction })
+            }
+        };
+
+        ShadowMap {
+            light_index,
+            resolution: res,
+            viewport: create_viewport_matrix(0.0, 0.0, res as f32, res as f32),
+            faces,
+            metric,
+            depth_bias: settings.depth_bias,
+            normal_bias: settings.normal_bias,
+            pcf_radius: settings.pcf_radius.max(0),
+        }
+    }
+
+    pub fn is_cube(&self) -> bool {
+        self.faces.len() == 6
+    }
+
+    /// Depth of a world-space point as seen from the light
+    pub fn light_depth(&self, p: Vector3) -> f32 {
+        match self.metric {
+            DepthMetric::Radial(origin) => (p - origin).length(),
+            DepthMetric::Planar { origin, direction } => (p - origin).dot(direction),
+        }
+    }
+
+    /// Keeps the closest depth per texel
+    pub fn write(&mut self, face: usize, x: u32, y: u32, depth: f32) {
+        if x < self.resolution && y < self.resolution {
+            let idx = (y * self.resolution + x) as usize;
+            let texel = &mut self.faces[face].depth[idx];
+            if depth < *texel {
+                *texel = depth;
+            }
+        }
+    }
+
+    /// Face a world point falls into (major axis of the light-to-point vector for cube maps)
+    fn face_for(&self, p: Vector3) -> usize {
+        if !self.is_cube() {
+            return 0;
+        }
+        let origin = match self.metric {
+            DepthMetric::Radial(origin) => origin,
+            DepthMetric::Planar { origin, .. } => origin,
+        };
+        let d = p - origin;
+        let (ax, ay, az) = (d.x.abs(), d.y.abs(), d.z.abs());
+        if ax >= ay && ax >= az {
+            if d.x >= 0.0 { 0 } else { 1 }
+        } else if ay >= az {
+            if d.y >= 0.0 { 2 } else { 3 }
+        } else if d.z >= 0.0 { 4 } else { 5 }
+    }
+
+    /// Fraction of the light reaching `point` in [0,1], PCF-filtered.
+    /// Points outside the light's frustum are treated as lit.
+    pub fn visibility(&self, point: Vector3, normal: Vector3) -> f32 {
+        let p = point + normal * self.normal_bias;
+        let face_idx = self.face_for(p);
+        let face = &self.faces[face_idx];
+
+        let view_p = multiply_matrix_vector4(&face.view, &Vector4::new(p.x, p.y, p.z, 1.0));
+        let clip = multiply_matrix_vector4(&face.projection, &view_p);
+        if clip.w <= 0.0 {
+            return 1.0;
+        }
+        let ndc = Vector4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+        if ndc.x.abs() > 1.0 || ndc.y.abs() > 1.0 {
+            return 1.0;
+        }
+        let texel = multiply_matrix_vector4(&self.viewport, &ndc);
+
+        let depth = self.light_depth(p) - self.depth_bias;
+        let max = self.resolution as i32 - 1;
+        let (cx, cy) = (texel.x as i32, texel.y as i32);
+        let mut lit = 0;
+        let mut taps = 0;
+        for dy in -self.pcf_radius..=self.pcf_radius {
+            for dx in -self.pcf_radius..=self.pcf_radius {
+                let x = (cx + dx).clamp(0, max) as u32;
+                let y = (cy + dy).clamp(0, max) as u32;
+                if depth <= face.depth[(y * self.resolution + x) as usize] {
+                    lit += 1;
+                }
+                taps += 1;
+            }
+        }
+        lit as f32 / taps as f32
+    }
+}
+
+/// Shadow visibility of `point` for the light at `light_index`; 1.0 if that light has no map.
+pub fn shadow_visibility(maps: &[ShadowMap], light_index: usize, point: Vector3, normal: Vector3) -> f32 {
+    maps.iter()
+        .find(|m| m.light_index == light_index)
+        .map_or(1.0, |m| m.visibility(point, normal))
+}
+
+/// Up vector that is never parallel to `dir`
+fn pick_up(dir: Vector3) -> Vector3 {
+    if dir.normalized().y.abs() > 0.99 { Vector3::new(0.0, 0.0, 1.0) } else { Vector3::new(0.0, 1.0, 0.0) }
+}
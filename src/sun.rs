@@ -0,0 +1,137 @@
+#![allow(dead_code)]
+
+use raylib::prelude::*;
+use crate::fragment::Fragment;
+use crate::gradient::GradientName;
+use crate::params::impl_params;
+use crate::uniforms::Uniforms;
+use crate::{fbm, value_noise3};
+
+/// Parameters of the sun: the emissive surface shader and the `SolarFlare` vertex
+/// displacement. The defaults are the values the shaders were tuned with.
+#[derive(Clone, Copy, Debug)]
+pub struct SunMaterial {
+    // Base look, combined with the runtime controls (arrow keys)
+    pub temperature: f32,        // 0 (rojo) … 1 (blanco/azulado)
+    pub intensity: f32,
+
+    // Surface turbulence
+    pub turbulence_scale: f32,   // noise frequency on the unit sphere
+    pub turbulence_octaves: u32,
+    pub turbulence_gain: f32,
+    pub loop_period: f32,        // seconds before the turbulence animation repeats
+    pub core_weight: f32,        // brightness towards the disc centre
+    pub turbulence_weight: f32,
+    pub temperature_weight: f32, // how much `temperature` shifts the colour ramp
+    pub color_range: f32,        // fraction of the colour ramp the surface spans
+    pub color_offset: f32,       // where on the ramp that span starts
+    pub ramp: GradientName,      // colour ramp, from cool to hot
+
+    // Emission spikes (energetic flicker)
+    pub spike_scale: f32,
+    pub spike_speed: f32,
+    pub emission: f32,           // weight of the surface intensity in the emission
+    pub spike_strength: f32,
+    pub max_emission: f32,
+
+    // Flares (vertex displacement along the normal)
+    pub flare_amplitude: f32,    // object units
+    pub flare_scale: f32,
+    pub flare_speed: f32,
+    pub flare_octaves: u32,
+}
+
+impl Default for SunMaterial {
+    fn default() -> Self {
+        SunMaterial {
+            temperature: 0.5,
+            intensity: 1.0,
+            turbulence_scale: 3.0,
+            turbulence_octaves: 5,
+            turbulence_gain: 0.55,
+            loop_period: 8.0,
+            core_weight: 0.7,
+            turbulence_weight: 0.6,
+            temperature_weight: 0.8,
+            color_range: 0.7,
+            color_offset: 0.0,
+            ramp: GradientName::TEMPERATURE,
+            spike_scale: 10.0,
+            spike_speed: 1.0,
+            emission: 0.6,
+            spike_strength: 0.8,
+            max_emission: 1.5,
+            flare_amplitude: 0.35,
+            flare_scale: 0.25,
+            flare_speed: 0.2,
+            flare_octaves: 4,
+        }
+    }
+}
+
+impl_params!(SunMaterial {
+    temperature: f32,
+    intensity: f32,
+    turbulence_scale: f32,
+    turbulence_octaves: u32,
+    turbulence_gain: f32,
+    loop_period: f32,
+    core_weight: f32,
+    turbulence_weight: f32,
+    temperature_weight: f32,
+    color_range: f32,
+    color_offset: f32,
+    ramp: gradient,
+    spike_scale: f32,
+    spike_speed: f32,
+    emission: f32,
+    spike_strength: f32,
+    max_emission: f32,
+    flare_amplitude: f32,
+    flare_scale: f32,
+    flare_speed: f32,
+    flare_octaves: u32,
+});
+
+/// Vertex stage: displaces along the pseudo-normal (normalized position) with animated FBM
+pub fn solar_flare(v: Vector3, m: &SunMaterial, time: f32) -> Vector3 {
+    let dir = if v.length() > 0.0 { v.normalized() } else { Vector3::new(0.0, 0.0, 1.0) };
+    let p = Vector3::new(v.x*m.flare_scale, v.y*m.flare_scale, v.z*m.flare_scale + time*m.flare_speed);
+    let n = fbm(p, m.flare_octaves as i32, 2.0, 0.5);
+    let flare = (n*2.0 - 1.0) * m.flare_amplitude;
+    v + dir * flare
+}
+
+pub fn sun_shader(fragment: &Fragment, m: &SunMaterial, u: &Uniforms) -> Vector3 {
+    // Use object-space direction for stable texturing on the sphere surface
+    let mut dir = fragment.obj_position;
+    let len = (dir.x*dir.x + dir.y*dir.y + dir.z*dir.z).sqrt();
+    if len > 0.0 { dir = Vector3::new(dir.x/len, dir.y/len, dir.z/len); }
+
+    // FBM turbulence driven by object-space, time-cycled
+    let p3 = Vector3::new(dir.x*m.turbulence_scale, dir.y*m.turbulence_scale, u.time % m.loop_period.max(1e-3));
+    let turb = fbm(p3, m.turbulence_octaves as i32, 2.0, m.turbulence_gain);
+
+    // Core intensity based on how close to the disc center it projects (approx with dir.z)
+    // dir.z ~ facing viewer if camera looks down -Z; use abs to be camera-agnostic
+    let facing = dir.z.abs();
+    let base_core = facing.clamp(0.0, 1.0);
+
+    // Runtime controls shift the material's own values: temp around 0.5, intensity as a factor
+    let temp = m.temperature + (u.temp - 0.5);
+    let intensity = ((base_core * m.core_weight + turb * m.turbulence_weight) * m.intensity * u.intensity).clamp(0.0, 1.0);
+
+    // Temperature picks the point along the colour ramp
+    let color_base = u.gradients.sample(m.ramp, ((intensity + temp*m.temperature_weight)*m.color_range + m.color_offset).clamp(0.0, 1.0));
+
+    // Emission spikes add energetic flicker
+    let t = u.time * m.spike_speed;
+    let spikes = (value_noise3(Vector3::new(dir.x*m.spike_scale + t*1.7, dir.y*m.spike_scale - t*1.3, t*0.5))*2.0-1.0).abs();
+    let emission = (m.emission*intensity + m.spike_strength*spikes).clamp(0.0, m.max_emission);
+
+    Vector3::new(
+        (color_base.x * emission).clamp(0.0, 1.0),
+        (color_base.y * emission).clamp(0.0, 1.0),
+        (color_base.z * emission).clamp(0.0, 1.0),
+    )
+}
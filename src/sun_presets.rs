@@ -0,0 +1,130 @@
+#![allow(dead_code)]
+
+use std::path::Path;
+use crate::scene::Scene;
+use crate::scene_file::load_material;
+use crate::sun::SunMaterial;
+use crate::{FragmentShader, VertexShader};
+
+/// Library of named sun looks: every `*.ron` file in the folder holding a `Solar`
+/// material, named after the file (`enana_roja.ron` is "enana roja"). One preset at a
+/// time replaces the material of every solar entity; cycling past the last one goes back
+/// to the materials of the scene.
+#[derive(Clone, Debug, Default)]
+pub struct SunPresets {
+    pub presets: Vec<(String, SunMaterial)>, // by file stem, in name order
+    pub current: Option<usize>,              // None: the scene's own materials
+    scene: Vec<(String, SunMaterial)>,       // solar materials as loaded, by entity
+}
+
+impl SunPresets {
+    /// Reads the presets in `dir` (none if it does not exist). Files that fail to load are
+    /// reported in the returned messages and left out.
+    pub fn load(dir: &Path) -> (SunPresets, Vec<String>) {
+        let mut library = SunPresets::default();
+        let mut errors = Vec::new();
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return (library, errors);
+        };
+        let mut files: Vec<_> = entries.filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "ron"))
+            .collect();
+        files.sort();
+        for path in files {
+            match SunPresets::read(&path) {
+                Ok(material) => library.presets.push((stem(&path), material)),
+                Err(error) => errors.push(error),
+            }
+        }
+        (library, errors)
+    }
+
+    fn read(path: &Path) -> Result<SunMaterial, String> {
+        match load_material(path).map_err(|e| e.to_string())? {
+            FragmentShader::Solar(material) => Ok(material),
+            _ => Err(format!("{}: un preset de sol debe ser un material Solar", path.display())),
+        }
+    }
+
+    /// Selects a preset by name, or loads a `.ron` file given by path and selects it
+    pub fn select(&mut self, name: &str) -> Result<(), String> {
+        if let Some(i) = self.presets.iter().position(|(n, _)| n == name || display_name(n) == name) {
+            self.current = Some(i);
+            return Ok(());
+        }
+        let path = Path::new(name);
+        if path.extension().is_some_and(|ext| ext == "ron") {
+            let material = SunPresets::read(path)?;
+            self.presets.push((stem(path), material));
+            self.current = Some(self.presets.len() - 1);
+            return Ok(());
+        }
+        let names: Vec<&str> = self.presets.iter().map(|(n, _)| n.as_str()).collect();
+        Err(format!("preset de sol desconocido: '{name}' (disponibles: {})", if names.is_empty() { "ninguno".to_string() } else { names.join(", ") }))
+    }
+
+    /// Adds a preset, or replaces the one with the same name, keeping the name order
+    pub fn add(&mut self, name: &str, material: SunMaterial) {
+        if let Some((_, existing)) = self.presets.iter_mut().find(|(n, _)| n == name) {
+            *existing = material;
+            return;
+        }
+        let i = self.presets.iter().position(|(n, _)| n.as_str() > name).unwrap_or(self.presets.len());
+        self.presets.insert(i, (name.to_string(), material));
+        match &mut self.current {
+            Some(current) if *current >= i => *current += 1,
+            _ => {}
+        }
+    }
+
+    pub fn current_name(&self) -> Option<String> {
+        self.current.map(|i| display_name(&self.presets[i].0))
+    }
+
+    /// Remembers the scene's solar materials for when the cycle comes back to them, then
+    /// re-applies the current preset. Call after every scene (re)load.
+    pub fn remember(&mut self, scene: &mut Scene) {
+        self.scene = scene.entities.iter()
+            .filter_map(|e| match e.fshader {
+                FragmentShader::Solar(material) => Some((e.name.clone(), material)),
+                _ => None,
+            })
+            .collect();
+        self.apply(scene);
+    }
+
+    /// Next preset, and after the last one the scene's own materials again
+    pub fn cycle(&mut self, scene: &mut Scene) {
+        self.current = match self.current {
+            None if !self.presets.is_empty() => Some(0),
+            Some(i) if i + 1 < self.presets.len() => Some(i + 1),
+            _ => None,
+        };
+        self.apply(scene);
+    }
+
+    /// Puts the current preset (or the remembered scene material) on every solar entity,
+    /// surface shader and flare vertex shader alike
+    pub fn apply(&self, scene: &mut Scene) {
+        for e in &mut scene.entities {
+            let FragmentShader::Solar(own) = e.fshader else { continue };
+            let material = match self.current {
+                Some(i) => self.presets[i].1,
+                None => self.scene.iter().find(|(name, _)| *name == e.name).map_or(own, |(_, m)| *m),
+            };
+            e.fshader = FragmentShader::Solar(material);
+            if let VertexShader::SolarFlare(_) = e.vshader {
+                e.vshader = VertexShader::SolarFlare(material);
+            }
+        }
+    }
+}
+
+fn stem(path: &Path) -> String {
+    path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+/// Name shown to the user: the file stem with spaces
+pub fn display_name(name: &str) -> String {
+    name.replace('_', " ")
+}
@@ -0,0 +1,212 @@
+#![allow(dead_code)]
+
+use raylib::prelude::*;
+use crate::matrix::create_transform_matrix;
+
+/// Unit quaternion for rotations (x, y, z = vector part, w = scalar part).
+/// Composition follows matrices: `a * b` rotates by `b` first, then by `a`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quat {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Quat {
+    pub fn identity() -> Self {
+        Quat { x: 0.0, y: 0.0, z: 0.0, w: 1.0 }
+    }
+
+    /// Rotation of `angle` radians about `axis` (need not be normalized)
+    pub fn from_axis_angle(axis: Vector3, angle: f32) -> Self {
+        let len = axis.length();
+        if len <= 1e-8 {
+            return Quat::identity();
+        }
+        let (s, c) = (angle * 0.5).sin_cos();
+        let a = axis / len;
+        Quat { x: a.x * s, y: a.y * s, z: a.z * s, w: c }
+    }
+
+    /// (axis, angle in [0, 2π)); the axis is +X for the identity
+    pub fn to_axis_angle(&self) -> (Vector3, f32) {
+        let q = self.normalized();
+        let angle = 2.0 * q.w.clamp(-1.0, 1.0).acos();
+        let s = (1.0 - q.w * q.w).max(0.0).sqrt();
+        if s < 1e-6 {
+            return (Vector3::new(1.0, 0.0, 0.0), 0.0);
+        }
+        (Vector3::new(q.x / s, q.y / s, q.z / s), angle)
+    }
+
+    /// Orientation whose -Z axis points along `forward` with +Y as close to `up` as
+    /// possible (the rotation part of a look-at)
+    pub fn look_rotation(forward: Vector3, up: Vector3) -> Self {
+        let back = -forward.normalized();
+        let right = up.cross(back).normalized();
+        let up = back.cross(right);
+        // Rotation matrix columns are right, up, back; convert with the largest diagonal pivot
+        let (m00, m11, m22) = (right.x, up.y, back.z);
+        let trace = m00 + m11 + m22;
+        let q = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Quat { w: 0.25 * s, x: (up.z - back.y) / s, y: (back.x - right.z) / s, z: (right.y - up.x) / s }
+        } else if m00 > m11 && m00 > m22 {
+            let s = (1.0 + m00 - m11 - m22).sqrt() * 2.0;
+            Quat { w: (up.z - back.y) / s, x: 0.25 * s, y: (up.x + right.y) / s, z: (back.x + right.z) / s }
+        } else if m11 > m22 {
+            let s = (1.0 + m11 - m00 - m22).sqrt() * 2.0;
+            Quat { w: (back.x - right.z) / s, x: (up.x + right.y) / s, y: 0.25 * s, z: (back.y + up.z) / s }
+        } else {
+            let s = (1.0 + m22 - m00 - m11).sqrt() * 2.0;
+            Quat { w: (right.y - up.x) / s, x: (back.x + right.z) / s, y: (back.y + up.z) / s, z: 0.25 * s }
+        };
+        q.normalized()
+    }
+
+    /// From Euler angles (x, y, z) in the order `create_model_matrix` always used:
+    /// R = Rz · Ry · Rx (X applied first)
+    pub fn from_euler(euler: Vector3) -> Self {
+        let qx = Quat::from_axis_angle(Vector3::new(1.0, 0.0, 0.0), euler.x);
+        let qy = Quat::from_axis_angle(Vector3::new(0.0, 1.0, 0.0), euler.y);
+        let qz = Quat::from_axis_angle(Vector3::new(0.0, 0.0, 1.0), euler.z);
+        qz * qy * qx
+    }
+
+    /// Back to Euler angles in the same Z·Y·X order; y stays in [-π/2, π/2]
+    pub fn to_euler(&self) -> Vector3 {
+        let q = self.normalized();
+        // Rotation matrix entries needed for the decomposition (row, column)
+        let r20 = 2.0 * (q.x * q.z - q.y * q.w);
+        let r21 = 2.0 * (q.y * q.z + q.x * q.w);
+        let r22 = 1.0 - 2.0 * (q.x * q.x + q.y * q.y);
+        let r10 = 2.0 * (q.x * q.y + q.z * q.w);
+        let r00 = 1.0 - 2.0 * (q.y * q.y + q.z * q.z);
+        let y = (-r20).clamp(-1.0, 1.0).asin();
+        if r20.abs() > 0.99999 {
+            // Gimbal lock: x and z rotate about the same axis, put it all in z
+            let r01 = 2.0 * (q.x * q.y - q.z * q.w);
+            let r11 = 1.0 - 2.0 * (q.x * q.x + q.z * q.z);
+            return Vector3::new(0.0, y, (-r01).atan2(r11));
+        }
+        Vector3::new(r21.atan2(r22), y, r10.atan2(r00))
+    }
+
+    pub fn dot(&self, other: Quat) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    pub fn length(&self) -> f32 {
+        self.dot(*self).sqrt()
+    }
+
+    pub fn normalized(&self) -> Self {
+        let len = self.length();
+        if len <= 1e-8 {
+            return Quat::identity();
+        }
+        Quat { x: self.x / len, y: self.y / len, z: self.z / len, w: self.w / len }
+    }
+
+    /// Inverse rotation (for unit quaternions)
+    pub fn conjugate(&self) -> Self {
+        Quat { x: -self.x, y: -self.y, z: -self.z, w: self.w }
+    }
+
+    pub fn rotate(&self, v: Vector3) -> Vector3 {
+        let u = Vector3::new(self.x, self.y, self.z);
+        let t = u.cross(v) * 2.0;
+        v + t * self.w + u.cross(t)
+    }
+
+    /// Spherical interpolation along the shortest arc, constant angular speed
+    pub fn slerp(&self, other: Quat, t: f32) -> Self {
+        let mut b = other;
+        let mut cos = self.dot(other);
+        if cos < 0.0 {
+            // q and -q are the same rotation; take the short way round
+            b = Quat { x: -b.x, y: -b.y, z: -b.z, w: -b.w };
+            cos = -cos;
+        }
+        let (ka, kb) = if cos > 0.9995 {
+            // Nearly parallel: linear interpolation is accurate and avoids dividing by ~0
+            (1.0 - t, t)
+        } else {
+            let angle = cos.acos();
+            let sin = angle.sin();
+            (((1.0 - t) * angle).sin() / sin, (t * angle).sin() / sin)
+        };
+        Quat {
+            x: self.x * ka + b.x * kb,
+            y: self.y * ka + b.y * kb,
+            z: self.z * ka + b.z * kb,
+            w: self.w * ka + b.w * kb,
+        }
+        .normalized()
+    }
+}
+
+impl std::ops::Mul for Quat {
+    type Output = Quat;
+
+    fn mul(self, b: Quat) -> Quat {
+        let a = self;
+        Quat {
+            x: a.w * b.x + a.x * b.w + a.y * b.z - a.z * b.y,
+            y: a.w * b.y - a.x * b.z + a.y * b.w + a.z * b.x,
+            z: a.w * b.z + a.x * b.y - a.y * b.x + a.z * b.w,
+            w: a.w * b.w - a.x * b.x - a.y * b.y - a.z * b.z,
+        }
+    }
+}
+
+/// Translation, rotation and per-axis scale of a model; applied as M = T · R · S
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub translation: Vector3,
+    pub rotation: Quat,
+    pub scale: Vector3,
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Transform {
+            translation: Vector3::zero(),
+            rotation: Quat::identity(),
+            scale: Vector3::one(),
+        }
+    }
+
+    pub fn new(translation: Vector3, rotation: Quat, scale: Vector3) -> Self {
+        Transform { translation, rotation, scale }
+    }
+
+    /// From Euler angles and a uniform scale, the old `create_model_matrix` arguments
+    pub fn from_euler(translation: Vector3, rotation: Vector3, scale: f32) -> Self {
+        Transform::new(translation, Quat::from_euler(rotation), Vector3::new(scale, scale, scale))
+    }
+
+    pub fn matrix(&self) -> Matrix {
+        create_transform_matrix(self.translation, self.rotation, self.scale)
+    }
+
+    pub fn transform_point(&self, p: Vector3) -> Vector3 {
+        self.translation + self.rotation.rotate(Vector3::new(p.x * self.scale.x, p.y * self.scale.y, p.z * self.scale.z))
+    }
+
+    /// Translation and scale interpolate linearly, rotation by slerp
+    pub fn interpolate(&self, other: &Transform, t: f32) -> Transform {
+        Transform {
+            translation: self.translation.lerp(other.translation, t),
+            rotation: self.rotation.slerp(other.rotation, t),
+            scale: self.scale.lerp(other.scale, t),
+        }
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::identity()
+    }
+}
@@ -1,31 +1,34 @@
 // src/triangle.rs
 use raylib::prelude::*;
 use crate::fragment::Fragment;
-use crate::light::Light;
 
-fn barycentric_coordinates(p_x: f32, p_y: f32, a: &Vector3, b: &Vector3, c: &Vector3)  -> (f32, f32, f32) {
-    let a_x = a.x;
-    let b_x = b.x;
-    let c_x = c.x;
-    let a_y = a.y;
-    let b_y = b.y;
-    let c_y = c.y;
-
-    let area = (b_y - c_y) * (a_x - c_x) + (c_x - b_x) * (a_y - c_y);
+/// Twice the signed area of the triangle p, q, (x, y): positive when (x, y) lies to the
+/// right of p→q as seen on screen (y pointing down), zero on the line through them.
+fn edge_function(p: &Vector3, q: &Vector3, x: f32, y: f32) -> f32 {
+    (q.x - p.x) * (y - p.y) - (q.y - p.y) * (x - p.x)
+}
 
-    if area.abs() < 1e-10  {
-        return (-1.0, -1.0, -1.0);
-    }
-    
-    let w = ((b_y - c_y) * (p_x - c_x) + (c_x - b_x) * (p_y - c_y)) / area;
-    let v = ((c_y - a_y) * (p_x - c_x) + (a_x - c_x) * (p_y - c_y)) / area;
-    let u = 1.0 - w - v;
+/// Top-left fill rule: a pixel centre lying exactly on an edge belongs to the triangle
+/// only if that edge is a top edge (horizontal, interior below) or a left edge, so a centre
+/// on an edge shared by two triangles is covered once. `(dx, dy)` runs along the edge
+/// with the interior on its right.
+fn is_top_left(dx: f32, dy: f32) -> bool {
+    (dy == 0.0 && dx > 0.0) || dy < 0.0
+}
 
-    (w, v, u)
+/// A vertex after the geometry stage: screen-space position (x, y in pixels, z = NDC depth)
+/// plus the object- and world-space positions the rasterizer interpolates for shading.
+#[derive(Clone, Copy)]
+pub struct Vertex {
+    pub screen: Vector3,
+    pub obj: Vector3,
+    pub world: Vector3,
 }
 
-pub fn triangle(v1: &Vector3, v2: &Vector3, v3: &Vector3, obj1: &Vector3, obj2: &Vector3, obj3: &Vector3, light: &Light) -> Vec<Fragment> {
+/// Rasterizes a triangle into fragments, clipped to a `width` x `height` target.
+pub fn triangle(a: &Vertex, b: &Vertex, c: &Vertex, width: u32, height: u32) -> Vec<Fragment> {
     let mut fragments: Vec<Fragment> = Vec::new();
+    let (v1, v2, v3) = (&a.screen, &b.screen, &c.screen);
 
     let a_x = v1.x;
     let b_x = v2.x;
@@ -34,34 +37,43 @@ pub fn triangle(v1: &Vector3, v2: &Vector3, v3: &Vector3, obj1: &Vector3, obj2:
     let b_y = v2.y;
     let c_y = v3.y;
 
-    let min_x = a_x.min(b_x).min(c_x).floor() as i32;
-    let min_y = a_y.min(b_y).min(c_y).floor() as i32;
+    let min_x = (a_x.min(b_x).min(c_x).floor() as i32).max(0);
+    let min_y = (a_y.min(b_y).min(c_y).floor() as i32).max(0);
 
-    let max_x = a_x.max(b_x).max(c_x).ceil() as i32;
-    let max_y = a_y.max(b_y).max(c_y).ceil() as i32;
+    let max_x = (a_x.max(b_x).max(c_x).ceil() as i32).min(width as i32 - 1);
+    let max_y = (a_y.max(b_y).max(c_y).ceil() as i32).min(height as i32 - 1);
 
-    let light = light.position.normalized();
-    for y in min_y..=max_y {
-        for x in min_x..=max_x {
-            let (w, v, u) = barycentric_coordinates(x  as f32, y as f32, v1, v2, v3);
+    // Flat world-space normal (counter-clockwise winding seen from the front)
+    let normal = (b.world - a.world).cross(c.world - a.world).normalized();
 
-            let depth = v1.z*w + v2.z*v + v3.z*u;
+    // Edges opposite a, b and c, whose edge functions are the barycentric weights of a, b
+    // and c times the area; `sign` turns either winding into interior-positive
+    let area = edge_function(v1, v2, v3.x, v3.y);
+    if area.abs() < 1e-10 {
+        return fragments;
+    }
+    let sign = area.signum();
+    let edges = [(v2, v3), (v3, v1), (v1, v2)];
+    let top_left = edges.map(|(p, q)| is_top_left((q.x - p.x) * sign, (q.y - p.y) * sign));
 
-            let intensity = v1.dot(light).max(0.0);
-            let final_color = Vector3::new(1.0, 1.0, 1.0)*intensity;
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            // Sampled at the pixel centre
+            let e = edges.map(|(p, q)| edge_function(p, q, x as f32 + 0.5, y as f32 + 0.5) * sign);
 
-            let ox = obj1.x*w + obj2.x*v + obj3.x*u;
-            let oy = obj1.y*w + obj2.y*v + obj3.y*u;
-            let oz = obj1.z*w + obj2.z*v + obj3.z*u;
-            let obj_pos = Vector3::new(ox, oy, oz);
+            if (0..3).all(|i| e[i] > 0.0 || (e[i] == 0.0 && top_left[i])) {
+                let (w, v, u) = (e[0] / area.abs(), e[1] / area.abs(), e[2] / area.abs());
+                let depth = v1.z*w + v2.z*v + v3.z*u;
+                let obj_pos = a.obj*w + b.obj*v + c.obj*u;
+                let world_pos = a.world*w + b.world*v + c.world*u;
 
-            if w >= 0.0 && v >= 0.0 && u >= 0.0 {
                 fragments.push(Fragment::new(
                     x as f32,
                     y as f32,
-                    final_color,
                     depth,
                     obj_pos,
+                    world_pos,
+                    normal,
                 ));
             }
         }
@@ -69,4 +81,30 @@ pub fn triangle(v1: &Vector3, v2: &Vector3, v3: &Vector3, obj1: &Vector3, obj2:
 
 
     fragments
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vertex(x: f32, y: f32) -> Vertex {
+        Vertex { screen: Vector3::new(x, y, 0.0), obj: Vector3::zero(), world: Vector3::new(x, y, 0.0) }
+    }
+
+    #[test]
+    fn shared_edges_are_covered_once() {
+        // A square split along its diagonal, with pixel centres lying exactly on every edge,
+        // in both windings
+        let (a, b, c, d) = (vertex(0.5, 0.5), vertex(8.5, 0.5), vertex(8.5, 8.5), vertex(0.5, 8.5));
+        for (first, second) in [((a, b, c), (a, c, d)), ((a, c, b), (a, d, c))] {
+            let mut hits = [[0; 10]; 10];
+            for (p, q, r) in [first, second] {
+                for f in triangle(&p, &q, &r, 10, 10) {
+                    hits[f.position.y as usize][f.position.x as usize] += 1;
+                }
+            }
+            assert!(hits.iter().flatten().all(|&n| n <= 1), "{hits:?}");
+            assert_eq!(hits.iter().flatten().sum::<i32>(), 64);
+        }
+    }
+}
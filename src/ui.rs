@@ -0,0 +1,350 @@
+#![allow(dead_code)]
+
+use raylib::prelude::*;
+use crate::font::{draw_text, text_width, GLYPH_WIDTH, LINE_HEIGHT};
+use crate::framebuffer::Framebuffer;
+
+/// Small immediate-mode UI drawn into the framebuffer: every frame the widgets are
+/// declared again, and each call both draws the widget and reports what the mouse did to
+/// it. Widgets are rows stacked top to bottom in a fixed-width column that scrolls with
+/// the wheel when it is taller than the window.
+///
+/// `UiState` is what survives between frames; `Ui` is one frame's layout pass.
+#[derive(Default)]
+pub struct UiState {
+    mouse: Vector2,
+    down: bool,
+    pressed: bool,
+    wheel: f32,
+    active: Option<u32>,   // widget being dragged, keeps the mouse until the button is released
+    outside: bool,         // the button went down outside the panel; ignore it until released
+    rect: Option<(i32, i32, i32, i32)>, // area covered last frame
+    content_height: i32,   // height of all rows last frame, scrolled or not
+    scroll: i32,
+}
+
+impl UiState {
+    /// Samples the mouse for this frame and tells whether the UI owns it: it is over the
+    /// panel (and was not pressed elsewhere first) or dragging one of its widgets.
+    /// Call before the rest of the input is read.
+    pub fn capture(&mut self, window: &RaylibHandle) -> bool {
+        let was_down = self.down;
+        self.mouse = window.get_mouse_position();
+        self.down = window.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT);
+        self.pressed = self.down && !was_down;
+        self.wheel = window.get_mouse_wheel_move();
+        let over = self.contains(self.mouse);
+        if self.pressed {
+            self.outside = !over;
+        }
+        if !self.down {
+            self.outside = false;
+            self.active = None;
+        }
+        self.active.is_some() || (over && !self.outside)
+    }
+
+    /// Forgets the panel area, e.g. while it is hidden
+    pub fn release(&mut self) {
+        self.rect = None;
+        self.active = None;
+    }
+
+    fn contains(&self, p: Vector2) -> bool {
+        self.rect.is_some_and(|(x, y, w, h)| {
+            p.x >= x as f32 && p.y >= y as f32 && p.x < (x + w) as f32 && p.y < (y + h) as f32
+        })
+    }
+
+    /// Starts a layout pass: a column `width` pixels wide at `x`, from `y` down to the
+    /// bottom of the framebuffer
+    pub fn begin<'a>(&'a mut self, framebuffer: &'a mut Framebuffer, x: i32, y: i32, width: i32, scale: i32) -> Ui<'a> {
+        let bottom = framebuffer.height as i32;
+        if self.wheel != 0.0 && self.contains(self.mouse) && !self.outside {
+            self.scroll -= (self.wheel * 3.0) as i32 * row_height(scale);
+        }
+        self.scroll = self.scroll.clamp(0, (self.content_height - (bottom - y)).max(0));
+        Ui { cursor: y - self.scroll, state: self, framebuffer, x, top: y, bottom, width, scale, next_id: 0, theme: Theme::default() }
+    }
+}
+
+/// Colours of the panel
+#[derive(Clone, Copy, Debug)]
+pub struct Theme {
+    pub background: Color,
+    pub text: Color,
+    pub dim: Color,
+    pub heading: Color,
+    pub track: Color,
+    pub fill: Color,
+    pub hover: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            background: Color::new(10, 12, 18, 200),
+            text: Color::new(225, 230, 240, 255),
+            dim: Color::new(150, 160, 175, 255),
+            heading: Color::new(90, 200, 255, 255),
+            track: Color::new(45, 50, 62, 255),
+            fill: Color::new(70, 120, 190, 255),
+            hover: Color::new(95, 150, 220, 255),
+        }
+    }
+}
+
+fn row_height(scale: i32) -> i32 {
+    (LINE_HEIGHT + 3) * scale
+}
+
+pub struct Ui<'a> {
+    state: &'a mut UiState,
+    framebuffer: &'a mut Framebuffer,
+    pub theme: Theme,
+    x: i32,
+    top: i32,
+    bottom: i32,
+    width: i32,
+    scale: i32,
+    cursor: i32, // top of the next row, scroll included
+    next_id: u32,
+}
+
+impl Ui<'_> {
+    /// Closes the pass and remembers the area the rows covered for `UiState::capture`
+    pub fn end(self) {
+        let height = (self.cursor - self.top).min(self.bottom - self.top);
+        self.state.content_height = self.cursor + self.state.scroll - self.top;
+        self.state.rect = Some((self.x, self.top, self.width, height.max(0)));
+    }
+
+    /// Lays out the next row: fills its background and returns its top, or None when it
+    /// is scrolled out of view (it is then neither drawn nor interactive)
+    fn row(&mut self) -> Option<i32> {
+        let y = self.cursor;
+        let h = row_height(self.scale);
+        self.cursor += h;
+        if y < self.top || y + h > self.bottom {
+            return None;
+        }
+        self.framebuffer.fill_rect(self.x, y, self.width, h, self.theme.background);
+        Some(y)
+    }
+
+    fn id(&mut self) -> u32 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn padding(&self) -> i32 {
+        2 * self.scale
+    }
+
+    fn text_y(&self, row: i32) -> i32 {
+        row + (row_height(self.scale) - LINE_HEIGHT * self.scale) / 2 + self.scale
+    }
+
+    fn hovered(&self, x: i32, y: i32, w: i32, h: i32) -> bool {
+        let m = self.state.mouse;
+        !self.state.outside && m.x >= x as f32 && m.y >= y as f32 && m.x < (x + w) as f32 && m.y < (y + h) as f32
+    }
+
+    /// Text cut to `columns` characters so it never spills into the next widget
+    fn clip(text: &str, columns: i32) -> String {
+        text.chars().take(columns.max(0) as usize).collect()
+    }
+
+    fn text(&mut self, text: &str, x: i32, row: i32, color: Color) {
+        let y = self.text_y(row);
+        draw_text(self.framebuffer, text, x, y, self.scale, color);
+    }
+
+    /// Width of the label column; the widget takes the rest
+    fn label_width(&self) -> i32 {
+        self.width * 11 / 20
+    }
+
+    pub fn heading(&mut self, text: &str) {
+        if let Some(y) = self.row() {
+            let columns = (self.width - 2 * self.padding()) / (GLYPH_WIDTH * self.scale);
+            let color = self.theme.heading;
+            self.text(&Self::clip(text, columns), self.x + self.padding(), y, color);
+        }
+    }
+
+    pub fn label(&mut self, text: &str) {
+        if let Some(y) = self.row() {
+            let columns = (self.width - 2 * self.padding()) / (GLYPH_WIDTH * self.scale);
+            let color = self.theme.dim;
+            self.text(&Self::clip(text, columns), self.x + self.padding(), y, color);
+        }
+    }
+
+    /// A row of equally wide buttons; returns the index of the one clicked
+    pub fn buttons(&mut self, labels: &[&str]) -> Option<usize> {
+        let y = self.row()?;
+        let (pad, h) = (self.padding(), row_height(self.scale));
+        let w = (self.width - pad) / labels.len().max(1) as i32;
+        let mut clicked = None;
+        for (i, label) in labels.iter().enumerate() {
+            let bx = self.x + pad + i as i32 * w;
+            let hovered = self.hovered(bx, y, w - pad, h);
+            let color = if hovered && self.state.down { self.theme.fill } else if hovered { self.theme.hover } else { self.theme.track };
+            self.framebuffer.fill_rect(bx, y + self.scale, w - pad, h - 2 * self.scale, color);
+            let label = Self::clip(label, (w - pad) / (GLYPH_WIDTH * self.scale));
+            let text_color = self.theme.text;
+            self.text(&label, bx + (w - pad - text_width(&label, self.scale)) / 2, y, text_color);
+            if hovered && self.state.pressed {
+                clicked = Some(i);
+            }
+        }
+        clicked
+    }
+
+    /// `< text >`: returns -1 or +1 when an arrow is clicked, 0 otherwise
+    pub fn stepper(&mut self, text: &str) -> i32 {
+        let Some(y) = self.row() else { return 0 };
+        let (pad, h, glyph) = (self.padding(), row_height(self.scale), GLYPH_WIDTH * self.scale);
+        let arrow = 3 * glyph;
+        let mut step = 0;
+        for (sign, ax, label) in [(-1, self.x + pad, "<"), (1, self.x + self.width - pad - arrow, ">")] {
+            let hovered = self.hovered(ax, y, arrow, h);
+            let color = if hovered { self.theme.hover } else { self.theme.track };
+            self.framebuffer.fill_rect(ax, y + self.scale, arrow, h - 2 * self.scale, color);
+            let text_color = self.theme.text;
+            self.text(label, ax + glyph, y, text_color);
+            if hovered && self.state.pressed {
+                step = sign;
+            }
+        }
+        let inner = self.width - 2 * (pad + arrow);
+        let text = Self::clip(text, inner / glyph);
+        let color = self.theme.text;
+        self.text(&text, self.x + pad + arrow + (inner - text_width(&text, self.scale)) / 2, y, color);
+        step
+    }
+
+    pub fn checkbox(&mut self, label: &str, value: &mut bool) -> bool {
+        let Some(y) = self.row() else { return false };
+        self.draw_label(label, y);
+        let size = LINE_HEIGHT * self.scale - 2 * self.scale;
+        let (bx, by) = (self.x + self.label_width(), y + (row_height(self.scale) - size) / 2);
+        let hovered = self.hovered(bx, y, self.width - self.label_width(), row_height(self.scale));
+        self.framebuffer.fill_rect(bx, by, size, size, if hovered { self.theme.hover } else { self.theme.track });
+        if *value {
+            let inset = 2 * self.scale;
+            self.framebuffer.fill_rect(bx + inset, by + inset, size - 2 * inset, size - 2 * inset, self.theme.text);
+        }
+        if hovered && self.state.pressed {
+            *value = !*value;
+            return true;
+        }
+        false
+    }
+
+    /// Horizontal slider over `min..=max`; the value may lie outside the range (set from a
+    /// file) and only changes when dragged. Returns true when it changed.
+    pub fn slider(&mut self, label: &str, value: &mut f32, min: f32, max: f32) -> bool {
+        let Some(y) = self.row() else { return false };
+        self.draw_label(label, y);
+        let (bx, bw) = (self.x + self.label_width(), self.width - self.label_width() - self.padding());
+        let text = format_value(*value);
+        let fill = self.theme.fill;
+        let Some(t) = self.bar(bx, bw, y, (*value - min) / (max - min), fill, Some(&text)) else { return false };
+        let v = min + t * (max - min);
+        let changed = v != *value;
+        *value = v;
+        changed
+    }
+
+    /// Integer slider; same as `slider` with the value rounded
+    pub fn slider_int(&mut self, label: &str, value: &mut u32, min: u32, max: u32) -> bool {
+        let Some(y) = self.row() else { return false };
+        self.draw_label(label, y);
+        let (bx, bw) = (self.x + self.label_width(), self.width - self.label_width() - self.padding());
+        let text = value.to_string();
+        let fill = self.theme.fill;
+        let t = (*value as f32 - min as f32) / (max - min).max(1) as f32;
+        let Some(t) = self.bar(bx, bw, y, t, fill, Some(&text)) else { return false };
+        let v = (min as f32 + t * (max - min) as f32).round() as u32;
+        let changed = v != *value;
+        *value = v;
+        changed
+    }
+
+    /// Colour picker: a swatch and one bar per channel (0 … 1)
+    pub fn color(&mut self, label: &str, value: &mut Vector3) -> bool {
+        let Some(y) = self.row() else { return false };
+        self.draw_label(label, y);
+        let pad = self.padding();
+        let (bx, bw) = (self.x + self.label_width(), self.width - self.label_width() - pad);
+        let h = row_height(self.scale);
+        let swatch = h - 2 * self.scale;
+        let to_byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        self.framebuffer.fill_rect(bx, y + self.scale, swatch, swatch, Color::new(to_byte(value.x), to_byte(value.y), to_byte(value.z), 255));
+        let channel_w = (bw - swatch - pad) / 3;
+        let channels = [
+            (&mut value.x, Color::new(200, 70, 70, 255)),
+            (&mut value.y, Color::new(70, 180, 90, 255)),
+            (&mut value.z, Color::new(70, 110, 220, 255)),
+        ];
+        let mut changed = false;
+        for (i, (channel, fill)) in channels.into_iter().enumerate() {
+            let cx = bx + swatch + pad + i as i32 * channel_w;
+            if let Some(t) = self.bar(cx, channel_w - pad, y, *channel, fill, None).filter(|&t| t != *channel) {
+                *channel = t;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Preview strip of a colour ramp, `color(t)` for t from 0 at the left to 1 at the right
+    pub fn ramp(&mut self, label: &str, color: impl Fn(f32) -> Vector3) {
+        let Some(y) = self.row() else { return };
+        self.draw_label(label, y);
+        let (bx, bw) = (self.x + self.label_width(), self.width - self.label_width() - self.padding());
+        let h = row_height(self.scale) - 2 * self.scale;
+        for i in 0..bw {
+            let c = color(i as f32 / (bw - 1).max(1) as f32);
+            let to_byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+            self.framebuffer.fill_rect(bx + i, y + self.scale, 1, h, Color::new(to_byte(c.x), to_byte(c.y), to_byte(c.z), 255));
+        }
+    }
+
+    fn draw_label(&mut self, label: &str, y: i32) {
+        let columns = (self.label_width() - 2 * self.padding()) / (GLYPH_WIDTH * self.scale);
+        let color = self.theme.text;
+        self.text(&Self::clip(label, columns), self.x + self.padding(), y, color);
+    }
+
+    /// Draggable bar filled to `t` (0 … 1) with optional centred text. While it is being
+    /// dragged returns the fraction under the mouse.
+    fn bar(&mut self, x: i32, w: i32, y: i32, t: f32, fill: Color, text: Option<&str>) -> Option<f32> {
+        let id = self.id();
+        let h = row_height(self.scale);
+        if self.hovered(x, y, w, h) && self.state.pressed {
+            self.state.active = Some(id);
+        }
+        let dragging = self.state.active == Some(id) && self.state.down;
+        let track = if dragging || self.hovered(x, y, w, h) { self.theme.hover } else { self.theme.track };
+        self.framebuffer.fill_rect(x, y + self.scale, w, h - 2 * self.scale, track);
+        self.framebuffer.fill_rect(x, y + self.scale, (t.clamp(0.0, 1.0) * w as f32).round() as i32, h - 2 * self.scale, fill);
+        if let Some(text) = text {
+            let text = Self::clip(text, w / (GLYPH_WIDTH * self.scale));
+            let color = self.theme.text;
+            self.text(&text, x + (w - text_width(&text, self.scale)) / 2, y, color);
+        }
+        dragging.then(|| ((self.state.mouse.x - x as f32) / w.max(1) as f32).clamp(0.0, 1.0))
+    }
+}
+
+/// Short display form: three significant digits or so
+fn format_value(v: f32) -> String {
+    match v.abs() {
+        a if a >= 100.0 => format!("{v:.0}"),
+        a if a >= 10.0 => format!("{v:.1}"),
+        _ => format!("{v:.3}"),
+    }
+}
@@ -1,11 +1,22 @@
 // uniforms.rs (si quieres en un archivo aparte) o al inicio de tu shader.rs
 use raylib::prelude::*;
+use crate::gradient::Gradients;
+use crate::light::Light;
+use crate::matrix::Matrix3;
+use crate::shadow::ShadowMap;
 
-pub struct Uniforms {
+pub struct Uniforms<'a> {
     pub time: f32,         // segundos
     pub resolution: Vector2, // tamaño ventana en píxeles
     pub temp: f32,
     pub intensity: f32,
+    pub lights: &'a [Light],      // luces de la escena en espacio mundo
+    pub shadows: &'a [ShadowMap], // mapas de sombra, indexados por luz
+    pub camera_position: Vector3, // ojo de la cámara en espacio mundo
+    pub ambient: Vector3,
+    pub gradients: &'a Gradients, // colour ramps that shaders sample by name
+    pub model: Matrix,            // matriz de modelo del objeto que se está dibujando
+    pub normal_matrix: Matrix3,   // inversa transpuesta de la parte lineal de `model`
 }
 
 // Convierte Color (0..255) a vec3 0..1
@@ -17,6 +28,12 @@ pub fn color_to_vec3(c: Color) -> Vector3 {
     )
 }
 
+// Convierte vec4 0..1 (rgb + alpha) a Color (0..255)
+pub fn vec4_to_color(v: Vector4) -> Color {
+    let c = vec3_to_color(Vector3::new(v.x, v.y, v.z));
+    Color::new(c.r, c.g, c.b, (v.w.clamp(0.0, 1.0) * 255.0) as u8)
+}
+
 // Convierte vec3 0..1 a Color (0..255)
 pub fn vec3_to_color(v: Vector3) -> Color {
     let r = (v.x.clamp(0.0, 1.0) * 255.0) as u8;
@@ -0,0 +1,52 @@
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Polls the modification time of a set of files. Cheap enough to run a few times per
+/// second from the render loop, and needs no OS notification backend.
+pub struct FileWatcher {
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+    pub interval: f32, // seconds between polls
+    elapsed: f32,
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl FileWatcher {
+    pub fn new(paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        let mut files: Vec<(PathBuf, Option<SystemTime>)> = Vec::new();
+        for path in paths {
+            if !files.iter().any(|(p, _)| *p == path) {
+                let stamp = modified(&path);
+                files.push((path, stamp));
+            }
+        }
+        FileWatcher { files, interval: 0.5, elapsed: 0.0 }
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(|(p, _)| p.as_path())
+    }
+
+    /// Advances by `frame_time` wall seconds and reports whether any file was modified,
+    /// created or deleted since the last poll
+    pub fn poll(&mut self, frame_time: f32) -> bool {
+        self.elapsed += frame_time;
+        if self.elapsed < self.interval {
+            return false;
+        }
+        self.elapsed = 0.0;
+        let mut changed = false;
+        for (path, stamp) in &mut self.files {
+            let now = modified(path);
+            if now != *stamp {
+                *stamp = now;
+                changed = true;
+            }
+        }
+        changed
+    }
+}